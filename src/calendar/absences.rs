@@ -0,0 +1,65 @@
+use crate::calendar::calendar_source::CalendarSource;
+use crate::calendar::meetings::{mail_address, parse_ical_date, parse_ical_duration};
+use crate::calendar::models::meeting::Meeting;
+use chrono::NaiveDate;
+use ical::parser::ical::component::IcalCalendar;
+use regex::Regex;
+
+/// Looks for an all-day absence (out of office, vacation, ...) on the date, and returns its title.
+/// An all-day event is an absence if Outlook flagged it as "out of office", or if its title matches the pattern.
+/// The filters of the calendar apply, so that the absences of the colleagues in a shared calendar can be ignored.
+pub fn find_absence(
+    source: &CalendarSource,
+    calendar: &IcalCalendar,
+    date: &NaiveDate,
+    absence_pattern: &Regex,
+) -> Option<String> {
+    calendar.events.iter().find_map(|event| {
+        // Only the fields used by the filters
+        let mut meeting = Meeting::new_default();
+        let mut start_date = None;
+        let mut end_date = None;
+        let mut duration = None;
+        let mut out_of_office = false;
+
+        for property in event.properties.iter() {
+            match property.name.as_str() {
+                "SUMMARY" => meeting.title = property.value.clone().unwrap_or_default(),
+                "DESCRIPTION" => meeting.description = property.value.clone().unwrap_or_default(),
+                "LOCATION" => meeting.location = property.value.clone().unwrap_or_default(),
+                "ORGANIZER" => meeting.organizer = mail_address(property),
+                "ATTENDEE" => meeting.attendees.extend(mail_address(property)),
+                "CATEGORIES" => meeting.categories.extend(
+                    property
+                        .value
+                        .iter()
+                        .flat_map(|value| value.split(','))
+                        .map(|category| category.trim().to_string()),
+                ),
+                "DTSTART" => start_date = parse_ical_date(property),
+                "DTEND" => end_date = parse_ical_date(property),
+                "DURATION" => duration = property.value.as_deref().and_then(parse_ical_duration),
+                "X-MICROSOFT-CDO-BUSYSTATUS" => {
+                    out_of_office = property.value.as_deref() == Some("OOF")
+                }
+                _ => {}
+            }
+        }
+
+        // Only all-day events are considered, and their end date is exclusive
+        let start_date = start_date?;
+        let end_date = end_date
+            .or_else(|| duration.map(|duration| start_date + duration))
+            .unwrap_or_else(|| start_date.succ());
+
+        if start_date <= *date
+            && *date < end_date
+            && (out_of_office || absence_pattern.is_match(&meeting.title))
+            && source.accepts(&meeting)
+        {
+            return Some(meeting.title);
+        }
+
+        None
+    })
+}
@@ -0,0 +1,90 @@
+use crate::calendar::calendar_source::{CalendarCredentials, CalendarSource};
+use crate::calendar::timezones::local_to_utc;
+use chrono::{Duration, NaiveDate};
+use chrono_tz::Tz;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Method};
+
+const CALDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:caldav";
+
+/// Runs a calendar-query REPORT on the CalDAV collection of the source, for the events of the date.
+/// Returns the VCALENDAR of every matching event, one after the other.
+pub async fn fetch_caldav_events(
+    client: &Client,
+    source: &CalendarSource,
+    date: &NaiveDate,
+    timezone: &Tz,
+) -> String {
+    let request = client
+        .request(Method::from_bytes(b"REPORT").unwrap(), &source.location)
+        .header("Depth", "1")
+        .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+        .body(calendar_query(date, timezone));
+    let request = match &source.credentials {
+        Some(CalendarCredentials::Basic { user, password }) => {
+            request.basic_auth(user, Some(password))
+        }
+        Some(CalendarCredentials::Bearer { token }) => request.bearer_auth(token),
+        None => request,
+    };
+
+    let body = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|e| {
+            panic!(
+                "Could not query the CalDAV calendar {}: {}",
+                source.location, e
+            )
+        })
+        .text()
+        .await
+        .unwrap();
+
+    calendar_data(&body).unwrap_or_else(|e| {
+        panic!(
+            "Invalid CalDAV response from {}: {}\n{}",
+            source.location, e, body
+        )
+    })
+}
+
+// The whole local day, converted to UTC as required by the time-range filter
+fn calendar_query(date: &NaiveDate, timezone: &Tz) -> String {
+    let format = "%Y%m%dT%H%M%SZ";
+    let start = local_to_utc(timezone, &date.and_hms(0, 0, 0));
+    let end = local_to_utc(timezone, &(*date + Duration::days(1)).and_hms(0, 0, 0));
+
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8" ?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="{}">
+  <D:prop>
+    <C:calendar-data/>
+  </D:prop>
+  <C:filter>
+    <C:comp-filter name="VCALENDAR">
+      <C:comp-filter name="VEVENT">
+        <C:time-range start="{}" end="{}"/>
+      </C:comp-filter>
+    </C:comp-filter>
+  </C:filter>
+</C:calendar-query>"#,
+        CALDAV_NAMESPACE,
+        start.format(format),
+        end.format(format)
+    )
+}
+
+// Content of the calendar-data elements of a multistatus response
+fn calendar_data(multistatus: &str) -> Result<String, roxmltree::Error> {
+    let document = roxmltree::Document::parse(multistatus)?;
+
+    Ok(document
+        .descendants()
+        .filter(|node| node.has_tag_name((CALDAV_NAMESPACE, "calendar-data")))
+        .filter_map(|node| node.text())
+        .map(|data| data.trim().to_string())
+        .collect::<Vec<String>>()
+        .join("\r\n"))
+}
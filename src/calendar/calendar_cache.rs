@@ -0,0 +1,112 @@
+use crate::calendar::calendar_source::{CalendarCredentials, CalendarSource};
+use crate::utils::hash::stable_hash;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+/// Local copies of the calendars downloaded from an url, to only download them again when they changed,
+/// and to still work offline
+#[derive(Debug, Clone)]
+pub struct CalendarCache {
+    pub dir: String,
+    // How old a copy can be used when the calendar can't be downloaded
+    pub max_staleness: Duration,
+}
+
+/// Validators of a cached calendar, sent back in the conditional requests
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedCalendarInfo {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    // Last time the copy was downloaded or known to be up to date
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct CachedCalendar {
+    pub content: String,
+    pub info: CachedCalendarInfo,
+}
+
+impl CalendarCache {
+    /// In the cache directory of the user (e.g. ~/.cache/tempo-bot/calendars on Linux)
+    pub fn default_dir() -> Option<String> {
+        dirs::cache_dir().map(|dir| {
+            dir.join("tempo-bot")
+                .join("calendars")
+                .to_string_lossy()
+                .to_string()
+        })
+    }
+
+    pub fn load(&self, source: &CalendarSource) -> Option<CachedCalendar> {
+        let info = fs::read_to_string(self.file_path(source, "json"))
+            .ok()
+            .and_then(|info| serde_json::from_str(&info).ok())?;
+        let content = fs::read_to_string(self.file_path(source, "ics")).ok()?;
+
+        Some(CachedCalendar { content, info })
+    }
+
+    pub fn save(&self, source: &CalendarSource, content: &str, info: &CachedCalendarInfo) {
+        fs::create_dir_all(&self.dir).unwrap_or_else(|e| {
+            panic!(
+                "Could not create the calendar cache directory {}: {}",
+                self.dir, e
+            )
+        });
+        self.save_info(source, info);
+        write_file(&self.file_path(source, "ics"), content);
+    }
+
+    pub fn save_info(&self, source: &CalendarSource, info: &CachedCalendarInfo) {
+        write_file(
+            &self.file_path(source, "json"),
+            &serde_json::to_string_pretty(info).unwrap(),
+        );
+    }
+
+    // Urls often contain a secret token, so they're hashed rather than used in the file names.
+    // The same url can return another calendar for other credentials, so they're part of the key
+    fn file_path(&self, source: &CalendarSource, extension: &str) -> String {
+        let identity = match &source.credentials {
+            Some(CalendarCredentials::Basic { user, .. }) => format!("user:{}", user),
+            Some(CalendarCredentials::Bearer { token }) => {
+                format!("token:{:016x}", stable_hash(token))
+            }
+            None => "".to_string(),
+        };
+        let key = format!("{}\n{}", source.location, identity);
+
+        Path::new(&self.dir)
+            .join(format!("{:016x}.{}", stable_hash(&key), extension))
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+impl CachedCalendar {
+    pub fn age(&self) -> Duration {
+        Utc::now() - self.info.checked_at
+    }
+}
+
+// Written to a temporary file moved into place, so a concurrent run never reads a partial copy.
+// Only the user can read it, as the calendars contain private events
+fn write_file(path: &str, content: &str) {
+    let temp_path = format!("{}.{}.tmp", path, std::process::id());
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    options
+        .open(&temp_path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .and_then(|_| fs::rename(&temp_path, path))
+        .unwrap_or_else(|e| panic!("Could not write {}: {}", path, e));
+}
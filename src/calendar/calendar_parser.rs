@@ -1,62 +1,242 @@
-use bytes::Buf;
+use crate::calendar::caldav::fetch_caldav_events;
+use crate::calendar::calendar_cache::{CachedCalendarInfo, CalendarCache};
+use crate::calendar::calendar_source::{CalendarCredentials, CalendarProtocol, CalendarSource};
+use crate::calendar::timezones::property_value;
+use crate::http::cassette::{Cassette, CassetteMode};
+use crate::http::http_client::network_client;
+use crate::utils::date::format_duration;
+use crate::NetworkConfig;
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
 use ical::parser::ical::component::IcalCalendar;
 use ical::IcalParser;
-use reqwest::get;
-use std::fs::File;
-use std::io::BufReader;
+use log::warn;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use std::fs;
 use url::Url;
 
 #[derive(Debug)]
 pub struct CalendarParser {
-    ics_file_name: String,
+    source: CalendarSource,
+    // Position of the calendar in the configuration, naming its recorded copy in the cassette
+    index: usize,
+    cassette: Option<Cassette>,
+    // Only for the calendars downloaded from an url
+    cache: Option<CalendarCache>,
+    // With the proxy and the CA bundle of the configuration
+    client: Client,
 }
 
 impl CalendarParser {
-    pub fn new(ics_file_name: &str) -> Self {
+    pub fn new(source: &CalendarSource, index: usize, network: &NetworkConfig) -> Self {
         Self {
-            ics_file_name: ics_file_name.to_string(),
+            source: source.clone(),
+            index,
+            cassette: network.cassette.clone(),
+            cache: None,
+            client: network_client(network),
         }
     }
 
-    pub async fn parse(&self) -> Option<IcalCalendar> {
-        let calendar;
-        if Url::parse(&self.ics_file_name).is_ok() {
-            calendar = self.parse_from_url().await;
-        } else {
-            calendar = self.parse_from_file();
+    pub fn with_cache(mut self, cache: Option<&CalendarCache>) -> Self {
+        self.cache = cache.cloned();
+        self
+    }
+
+    /// The date and the time zone only restrict the events returned by a CalDAV server
+    pub async fn parse(&self, date: &NaiveDate, timezone: &Tz) -> Option<IcalCalendar> {
+        let content = match &self.cassette {
+            Some(cassette) if cassette.mode == CassetteMode::Replay => {
+                cassette.load_calendar(self.index)
+            }
+            _ if self.source.protocol == CalendarProtocol::CalDav => {
+                fetch_caldav_events(&self.client, &self.source, date, timezone).await
+            }
+            _ if Url::parse(&self.source.location).is_ok() => self.read_from_url().await,
+            _ => self.read_from_file(),
+        };
+
+        if let Some(cassette) = &self.cassette {
+            if cassette.mode == CassetteMode::Record {
+                cassette.save_calendar(self.index, &content);
+            }
         }
 
-        calendar
+        // Merged feeds (and CalDAV servers, with a calendar for each event) contain several calendars
+        merge_calendars(
+            components(&content, "VCALENDAR")
+                .into_iter()
+                .filter_map(parse_calendar)
+                .collect(),
+        )
     }
 
-    fn parse_from_file(&self) -> Option<IcalCalendar> {
-        let buf = BufReader::new(
-            File::open(&self.ics_file_name)
-                .unwrap_or_else(|_| panic!("Could not read ICS file from {}", self.ics_file_name)),
-        );
-        let mut reader = IcalParser::new(buf);
+    fn read_from_file(&self) -> String {
+        fs::read_to_string(&self.source.location)
+            .unwrap_or_else(|_| panic!("Could not read ICS file from {}", self.source.location))
+    }
 
-        if let Some(line) = reader.next() {
-            return Some(line.unwrap());
+    /// Downloads the calendar, unless the cached copy is still up to date.
+    /// The cached copy is also used when the download fails, if it's not too old.
+    async fn read_from_url(&self) -> String {
+        let url = &self.source.location;
+        let cached = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.load(&self.source));
+
+        let mut request = self.client.get(url);
+        request = match &self.source.credentials {
+            Some(CalendarCredentials::Basic { user, password }) => {
+                request.basic_auth(user, Some(password))
+            }
+            Some(CalendarCredentials::Bearer { token }) => request.bearer_auth(token),
+            None => request,
+        };
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.info.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.info.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
         }
 
-        None
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        // `None` when the cached copy is up to date. Reading the body can fail too, after a successful response
+        let downloaded = match response {
+            Ok(response) if response.status() == StatusCode::NOT_MODIFIED && cached.is_some() => {
+                Ok(None)
+            }
+            Ok(response) => {
+                let header = |name| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .map(|value| value.to_string())
+                };
+                let info = CachedCalendarInfo {
+                    etag: header(ETAG),
+                    last_modified: header(LAST_MODIFIED),
+                    checked_at: Utc::now(),
+                };
+                response.text().await.map(|content| Some((content, info)))
+            }
+            Err(e) => Err(e),
+        };
+
+        match (downloaded, cached, &self.cache) {
+            (Ok(None), Some(mut cached), Some(cache)) => {
+                cached.info.checked_at = Utc::now();
+                cache.save_info(&self.source, &cached.info);
+                cached.content
+            }
+            (Ok(None), _, _) => unreachable!("Only a cached calendar can be up to date"),
+            (Ok(Some((content, info))), _, cache) => {
+                if let Some(cache) = cache {
+                    cache.save(&self.source, &content, &info);
+                }
+                content
+            }
+            (Err(e), Some(cached), Some(cache)) if cached.age() <= cache.max_staleness => {
+                warn!(
+                    "Could not download ICS file from {} ({}), using the copy cached {} ago",
+                    url,
+                    e,
+                    format_duration(&(cached.age().num_seconds() as i32))
+                );
+                cached.content
+            }
+            (Err(e), _, _) => panic!("Could not download ICS file from {}: {}", url, e),
+        }
     }
+}
 
-    async fn parse_from_url(&self) -> Option<IcalCalendar> {
-        let buf = get(&self.ics_file_name)
-            .await
-            .unwrap_or_else(|_| panic!("Could not download ICS file from {}", self.ics_file_name))
-            .bytes()
-            .await
-            .unwrap()
-            .reader();
-        let mut reader = IcalParser::new(buf);
+/// Merges the events and the time zones of several calendars
+pub fn merge_calendars(calendars: Vec<IcalCalendar>) -> Option<IcalCalendar> {
+    calendars.into_iter().reduce(|mut merged, calendar| {
+        merged.events.extend(calendar.events);
+        for timezone in calendar.timezones {
+            if !merged.timezones.iter().any(|known| {
+                property_value(&known.properties, "TZID")
+                    == property_value(&timezone.properties, "TZID")
+            }) {
+                merged.timezones.push(timezone);
+            }
+        }
+        merged
+    })
+}
 
-        if let Some(line) = reader.next() {
-            return Some(line.unwrap());
+// A calendar that can't be parsed is parsed again event by event, so only the broken events are skipped
+fn parse_calendar(content: &str) -> Option<IcalCalendar> {
+    let error = match IcalParser::new(content.as_bytes()).next()? {
+        Ok(calendar) => return Some(calendar),
+        Err(error) => error,
+    };
+
+    let events = components(content, "VEVENT");
+    let without_events = events.iter().fold(content.to_string(), |content, event| {
+        content.replacen(event, "", 1)
+    });
+    let mut calendar = match IcalParser::new(without_events.as_bytes()).next()? {
+        Ok(calendar) => calendar,
+        Err(_) => {
+            warn!("Skipped an invalid calendar: {}", error);
+            return None;
+        }
+    };
+
+    for event in events {
+        let event_calendar = format!("BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n", event);
+        match IcalParser::new(event_calendar.as_bytes()).next() {
+            Some(Ok(parsed)) => calendar.events.extend(parsed.events),
+            Some(Err(error)) => warn!("Skipped the invalid event {}: {}", event_uid(event), error),
+            None => {}
+        }
+    }
+
+    Some(calendar)
+}
+
+// Every `BEGIN:<name>` to `END:<name>` block of the content, with its line breaks
+fn components<'a>(content: &'a str, name: &str) -> Vec<&'a str> {
+    let mut components = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if start.is_none() && trimmed.eq_ignore_ascii_case(&format!("BEGIN:{}", name)) {
+            start = Some(offset);
         }
+        offset += line.len();
+        if let Some(component_start) = start {
+            if trimmed.eq_ignore_ascii_case(&format!("END:{}", name)) {
+                components.push(&content[component_start..offset]);
+                start = None;
+            }
+        }
+    }
 
-        None
+    // Truncated content
+    if let Some(component_start) = start {
+        components.push(&content[component_start..]);
     }
+
+    components
+}
+
+fn event_uid(event: &str) -> String {
+    event
+        .lines()
+        .find_map(|line| line.strip_prefix("UID:"))
+        .map(|uid| uid.trim().to_string())
+        .unwrap_or_else(|| "(without UID)".to_string())
 }
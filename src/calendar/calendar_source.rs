@@ -0,0 +1,291 @@
+use crate::calendar::meeting_rule::MeetingRuleField;
+use crate::calendar::models::meeting::Meeting;
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+const CALDAV_PREFIX: &str = "caldav+";
+
+/// A `<field>:<regex>` filter on the meetings of a calendar
+#[derive(Debug, Clone)]
+pub struct MeetingFilter {
+    pub field: MeetingRuleField,
+    pub regex: Regex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarProtocol {
+    // ICS file, local or downloaded
+    Ics,
+    // CalDAV collection, queried for the events of the day only
+    CalDav,
+}
+
+/// Credentials sent when downloading a calendar from an url
+#[derive(Clone)]
+pub enum CalendarCredentials {
+    Basic { user: String, password: String },
+    Bearer { token: String },
+}
+
+/// A calendar file, url or CalDAV collection (url prefixed by `caldav+`), with the options applying to its meetings only:
+/// `<file or url>[;default-issue=<issue key>][;include=<field>:<regex>][;exclude=<field>:<regex>][;user=<user>;password=<password>][;token=<token>]`.
+/// The secrets can be read from environment variables instead (`password-env=<variable>`, `token-env=<variable>`),
+/// and a `;` inside a value is escaped as `\;`
+#[derive(Debug, Clone)]
+pub struct CalendarSource {
+    pub protocol: CalendarProtocol,
+    // File or url, without the protocol prefix
+    pub location: String,
+    // Issue of the meetings of this calendar without any issue key, before the global default issue
+    pub default_issue: Option<String>,
+    // If any, only the meetings matching one of them are logged
+    pub include: Vec<MeetingFilter>,
+    // Meetings matching any of them are never logged
+    pub exclude: Vec<MeetingFilter>,
+    pub credentials: Option<CalendarCredentials>,
+}
+
+impl MeetingFilter {
+    pub fn matches(&self, meeting: &Meeting) -> bool {
+        self.field.matches(&self.regex, meeting)
+    }
+}
+
+impl FromStr for MeetingFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, regex) = s.split_once(':').ok_or_else(|| {
+            format!(
+                "Invalid meeting filter '{}'. Expected format: '<field>:<regex>'",
+                s
+            )
+        })?;
+
+        Ok(Self {
+            field: field.parse()?,
+            regex: Regex::new(regex)
+                .map_err(|e| format!("Invalid regex in meeting filter '{}': {}", s, e))?,
+        })
+    }
+}
+
+impl fmt::Display for MeetingFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.field.name(), self.regex.as_str())
+    }
+}
+
+// Never print the secrets in the logs
+impl fmt::Debug for CalendarCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalendarCredentials::Basic { user, .. } => write!(f, "Basic({}:***)", user),
+            CalendarCredentials::Bearer { .. } => write!(f, "Bearer(***)"),
+        }
+    }
+}
+
+impl CalendarSource {
+    pub fn new(location: &str) -> Self {
+        let (protocol, location) = match location.strip_prefix(CALDAV_PREFIX) {
+            Some(url) => (CalendarProtocol::CalDav, url),
+            None => (CalendarProtocol::Ics, location),
+        };
+
+        Self {
+            protocol,
+            location: location.to_string(),
+            default_issue: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            credentials: None,
+        }
+    }
+
+    /// Whether the meetings of this calendar should be logged, according to its include and exclude filters
+    pub fn accepts(&self, meeting: &Meeting) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|filter| filter.matches(meeting)))
+            && !self.exclude.iter().any(|filter| filter.matches(meeting))
+    }
+}
+
+impl FromStr for CalendarSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = split_options(s);
+        let mut parts = parts.iter();
+        let location = parts.next().map(|part| part.trim()).unwrap_or_default();
+        if location.is_empty() {
+            return Err(format!("Missing calendar file or url in '{}'", s));
+        }
+
+        let mut source = CalendarSource::new(location);
+        let mut user = None;
+        let mut password = None;
+        let mut token = None;
+
+        for part in parts.filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| {
+                format!(
+                    "Invalid calendar option '{}'. Expected format: '<option>=<value>'",
+                    part
+                )
+            })?;
+
+            match key.trim().to_lowercase().as_str() {
+                "default-issue" => source.default_issue = Some(value.trim().to_string()),
+                "include" => source.include.push(value.parse()?),
+                "exclude" => source.exclude.push(value.parse()?),
+                "user" => user = Some(value.to_string()),
+                "password" => password = Some(value.to_string()),
+                "password-env" => password = Some(env_var(value)?),
+                "token" => token = Some(value.to_string()),
+                "token-env" => token = Some(env_var(value)?),
+                _ => return Err(format!(
+                    "Unknown calendar option '{}'. Expected one of: default-issue, include, exclude, user, password, password-env, token, token-env",
+                    key
+                )),
+            }
+        }
+
+        source.credentials = match (user, password, token) {
+            (None, None, None) => None,
+            (Some(user), Some(password), None) => {
+                Some(CalendarCredentials::Basic { user, password })
+            }
+            (None, None, Some(token)) => Some(CalendarCredentials::Bearer { token }),
+            _ => {
+                return Err(format!(
+                    "Invalid credentials for calendar '{}'. Expected either 'user' and 'password', or 'token'",
+                    location
+                ))
+            }
+        };
+
+        Ok(source)
+    }
+}
+
+impl fmt::Display for CalendarSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.protocol == CalendarProtocol::CalDav {
+            write!(f, "{}", CALDAV_PREFIX)?;
+        }
+        write!(f, "{}", escape(&self.location))?;
+        if let Some(default_issue) = &self.default_issue {
+            write!(f, ";default-issue={}", escape(default_issue))?;
+        }
+        for filter in self.include.iter() {
+            write!(f, ";include={}", escape(&filter.to_string()))?;
+        }
+        for filter in self.exclude.iter() {
+            write!(f, ";exclude={}", escape(&filter.to_string()))?;
+        }
+        match &self.credentials {
+            Some(CalendarCredentials::Basic { user, .. }) => {
+                write!(f, ";user={};password=***", escape(user))
+            }
+            Some(CalendarCredentials::Bearer { .. }) => write!(f, ";token=***"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Splits the location and the options on the `;` that are not escaped as `\;`
+fn split_options(s: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&';') => {
+                parts.last_mut().unwrap().push(';');
+                chars.next();
+            }
+            ';' => parts.push(String::new()),
+            _ => parts.last_mut().unwrap().push(c),
+        }
+    }
+
+    parts
+}
+
+fn escape(value: &str) -> String {
+    value.replace(';', "\\;")
+}
+
+fn env_var(name: &str) -> Result<String, String> {
+    std::env::var(name.trim()).map_err(|_| {
+        format!(
+            "The environment variable '{}' of the calendar credentials is not set",
+            name.trim()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_calendar_options() {
+        let source: CalendarSource =
+            "caldav+https://dav.company.com/john/work/;default-issue=ABC-1;include=title:(?i)sync;token=secret"
+                .parse()
+                .unwrap();
+
+        assert_eq!(source.protocol, CalendarProtocol::CalDav);
+        assert_eq!(source.location, "https://dav.company.com/john/work/");
+        assert_eq!(source.default_issue.as_deref(), Some("ABC-1"));
+        assert_eq!(source.include[0].field, MeetingRuleField::Title);
+        assert!(matches!(
+            source.credentials,
+            Some(CalendarCredentials::Bearer { ref token }) if token == "secret"
+        ));
+        assert_eq!(
+            source.to_string(),
+            "caldav+https://dav.company.com/john/work/;default-issue=ABC-1;include=title:(?i)sync;token=***"
+        );
+    }
+
+    #[test]
+    fn parses_the_escaped_calendar_options() {
+        let source: CalendarSource =
+            r"https://calendar.company.com/team.ics?a=1\;b=2;exclude=title:lunch\;;user=john;password=se\;cret"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            source.location,
+            "https://calendar.company.com/team.ics?a=1;b=2"
+        );
+        assert_eq!(source.exclude[0].regex.as_str(), "lunch;");
+        assert!(matches!(
+            source.credentials,
+            Some(CalendarCredentials::Basic { ref password, .. }) if password == "se;cret"
+        ));
+        assert_eq!(
+            source.to_string(),
+            r"https://calendar.company.com/team.ics?a=1\;b=2;exclude=title:lunch\;;user=john;password=***"
+        );
+    }
+
+    #[test]
+    fn rejects_the_invalid_calendar_options() {
+        for source in [
+            "",
+            ";default-issue=ABC-1",
+            "team.ics;default-issue",
+            "team.ics;color=red",
+            "team.ics;include=title",
+            "team.ics;exclude=room:lunch",
+            "team.ics;user=john",
+            "team.ics;user=john;password=secret;token=secret",
+            "team.ics;token-env=TEMPO_BOT_TEST_UNSET_TOKEN",
+        ] {
+            assert!(source.parse::<CalendarSource>().is_err(), "{}", source);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::calendar::models::meeting::Meeting;
+use crate::utils::date::format_duration;
+use chrono::Duration;
+use log::info;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeetingRounding {
+    // Exact durations
+    None,
+    Nearest,
+    Up,
+}
+
+/// How the durations of the meetings are logged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeetingDurations {
+    pub rounding: MeetingRounding,
+    // The rounded durations are multiples of this increment
+    pub increment_seconds: i32,
+    // Shorter meetings are merged into the misc issue, or not logged at all
+    pub min_duration_seconds: i32,
+    pub misc_issue: Option<String>,
+}
+
+impl FromStr for MeetingRounding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(MeetingRounding::None),
+            "nearest" => Ok(MeetingRounding::Nearest),
+            "up" => Ok(MeetingRounding::Up),
+            _ => Err(format!(
+                "Unknown meeting rounding '{}'. Expected one of: none, nearest, up",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MeetingRounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeetingRounding::None => write!(f, "none"),
+            MeetingRounding::Nearest => write!(f, "nearest"),
+            MeetingRounding::Up => write!(f, "up"),
+        }
+    }
+}
+
+impl Default for MeetingDurations {
+    fn default() -> Self {
+        Self {
+            rounding: MeetingRounding::None,
+            increment_seconds: 30 * 60,
+            min_duration_seconds: 0,
+            misc_issue: None,
+        }
+    }
+}
+
+impl MeetingDurations {
+    /// Merges (or removes) the too short meetings, then rounds the durations
+    pub fn apply(&self, meetings: Vec<Meeting>) -> Vec<Meeting> {
+        let (mut meetings, short_meetings): (Vec<Meeting>, Vec<Meeting>) = meetings
+            .into_iter()
+            .partition(|meeting| meeting.duration >= self.min_duration_seconds);
+
+        match &self.misc_issue {
+            Some(misc_issue) if !short_meetings.is_empty() => {
+                meetings.push(misc_meeting(short_meetings, misc_issue))
+            }
+            _ => {
+                for meeting in short_meetings.iter() {
+                    info!(
+                        "Skipped meeting '{}' (shorter than {})",
+                        meeting.title,
+                        format_duration(&self.min_duration_seconds)
+                    );
+                }
+            }
+        }
+
+        meetings
+            .into_iter()
+            .filter_map(|mut meeting| {
+                meeting.duration = self.round(meeting.duration);
+                // The later steps (e.g. the overtime) rely on the end time
+                meeting.end_time = meeting.start_time + Duration::seconds(meeting.duration.into());
+                if meeting.duration == 0 {
+                    info!("Skipped meeting '{}' (rounded to 0)", meeting.title);
+                    return None;
+                }
+                Some(meeting)
+            })
+            .collect()
+    }
+
+    pub fn round(&self, duration: i32) -> i32 {
+        let increment = self.increment_seconds;
+        match self.rounding {
+            _ if increment <= 0 => duration,
+            MeetingRounding::None => duration,
+            MeetingRounding::Nearest => (duration + increment / 2) / increment * increment,
+            MeetingRounding::Up => (duration + increment - 1) / increment * increment,
+        }
+    }
+}
+
+// A single meeting for all the short meetings, starting with the first one
+fn misc_meeting(mut short_meetings: Vec<Meeting>, misc_issue: &str) -> Meeting {
+    short_meetings.sort_by_key(|meeting| meeting.start_time);
+    let titles: Vec<&str> = short_meetings
+        .iter()
+        .map(|meeting| meeting.title.as_str())
+        .collect();
+
+    let duration: i32 = short_meetings.iter().map(|meeting| meeting.duration).sum();
+    Meeting {
+        title: format!("Misc meetings: {}", titles.join(", ")),
+        tempo_code: Some(misc_issue.to_string()),
+        duration,
+        end_time: short_meetings[0].start_time + Duration::seconds(duration.into()),
+        ..short_meetings[0].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn durations(rounding: &str, increment_minutes: i32) -> MeetingDurations {
+        MeetingDurations {
+            rounding: rounding.parse().unwrap(),
+            increment_seconds: increment_minutes * 60,
+            ..MeetingDurations::default()
+        }
+    }
+
+    #[test]
+    fn rounds_the_durations_to_the_increment() {
+        let nearest = durations("nearest", 15);
+        assert_eq!(nearest.round(7 * 60 + 29), 0);
+        assert_eq!(nearest.round(7 * 60 + 30), 15 * 60);
+        assert_eq!(nearest.round(25 * 60), 30 * 60);
+
+        let up = durations("up", 15);
+        assert_eq!(up.round(0), 0);
+        assert_eq!(up.round(1), 15 * 60);
+        assert_eq!(up.round(15 * 60), 15 * 60);
+        assert_eq!(up.round(15 * 60 + 1), 30 * 60);
+
+        assert_eq!(durations("none", 15).round(25 * 60), 25 * 60);
+        // Without any increment, the durations are kept
+        assert_eq!(durations("up", 0).round(25 * 60), 25 * 60);
+    }
+
+    #[test]
+    fn parses_the_meeting_roundings() {
+        assert_eq!(" Up".parse::<MeetingRounding>(), Ok(MeetingRounding::Up));
+        assert_eq!(MeetingRounding::Nearest.to_string(), "nearest");
+        assert!("down".parse::<MeetingRounding>().is_err());
+    }
+}
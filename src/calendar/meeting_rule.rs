@@ -0,0 +1,161 @@
+use crate::calendar::models::meeting::Meeting;
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeetingRuleField {
+    Title,
+    Description,
+    Location,
+    // Email address of the organizer
+    Organizer,
+    Category,
+    // Domain of the email address of any attendee (or of the organizer)
+    AttendeeDomain,
+}
+
+/// A `<field>:<regex>=<issue key>` rule, mapping the meetings with a field matching the regex to the issue
+#[derive(Debug, Clone)]
+pub struct MeetingRule {
+    pub field: MeetingRuleField,
+    pub regex: Regex,
+    pub issue_key: String,
+}
+
+impl MeetingRuleField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeetingRuleField::Title => "title",
+            MeetingRuleField::Description => "description",
+            MeetingRuleField::Location => "location",
+            MeetingRuleField::Organizer => "organizer",
+            MeetingRuleField::Category => "category",
+            MeetingRuleField::AttendeeDomain => "attendee-domain",
+        }
+    }
+}
+
+impl FromStr for MeetingRuleField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "title" => Ok(MeetingRuleField::Title),
+            "description" => Ok(MeetingRuleField::Description),
+            "location" => Ok(MeetingRuleField::Location),
+            "organizer" => Ok(MeetingRuleField::Organizer),
+            "category" => Ok(MeetingRuleField::Category),
+            "attendee-domain" => Ok(MeetingRuleField::AttendeeDomain),
+            _ => Err(format!(
+                "Unknown meeting rule field '{}'. Expected one of: title, description, location, organizer, category, attendee-domain",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for MeetingRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The regex can contain ':' and '=', but neither the field nor the issue key
+        let invalid_rule = || {
+            format!(
+                "Invalid meeting rule '{}'. Expected format: '<field>:<regex>=<issue key>'",
+                s
+            )
+        };
+        let (field, rest) = s.split_once(':').ok_or_else(invalid_rule)?;
+        let (regex, issue_key) = rest.rsplit_once('=').ok_or_else(invalid_rule)?;
+        if regex.is_empty() || issue_key.trim().is_empty() {
+            return Err(invalid_rule());
+        }
+
+        Ok(Self {
+            field: field.parse()?,
+            regex: Regex::new(regex)
+                .map_err(|e| format!("Invalid regex in meeting rule '{}': {}", s, e))?,
+            issue_key: issue_key.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for MeetingRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}={}",
+            self.field.name(),
+            self.regex.as_str(),
+            self.issue_key
+        )
+    }
+}
+
+impl MeetingRule {
+    pub fn matches(&self, meeting: &Meeting) -> bool {
+        self.field.matches(&self.regex, meeting)
+    }
+}
+
+impl MeetingRuleField {
+    /// Whether the field of the meeting matches the regex
+    pub fn matches(&self, regex: &Regex, meeting: &Meeting) -> bool {
+        match self {
+            MeetingRuleField::Title => regex.is_match(&meeting.title),
+            MeetingRuleField::Description => regex.is_match(&meeting.description),
+            MeetingRuleField::Location => regex.is_match(&meeting.location),
+            MeetingRuleField::Organizer => meeting
+                .organizer
+                .as_ref()
+                .map(|organizer| regex.is_match(organizer))
+                .unwrap_or(false),
+            MeetingRuleField::Category => meeting
+                .categories
+                .iter()
+                .any(|category| regex.is_match(category)),
+            MeetingRuleField::AttendeeDomain => meeting
+                .attendees
+                .iter()
+                .chain(meeting.organizer.iter())
+                .filter_map(|address| address.split_once('@'))
+                .any(|(_, domain)| regex.is_match(domain)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_meeting_rules() {
+        let rule: MeetingRule = "Attendee-Domain:^client\\.com$= CLI-42".parse().unwrap();
+        assert_eq!(rule.field, MeetingRuleField::AttendeeDomain);
+        assert_eq!(rule.regex.as_str(), "^client\\.com$");
+        assert_eq!(rule.issue_key, "CLI-42");
+        assert_eq!(rule.to_string(), "attendee-domain:^client\\.com$=CLI-42");
+
+        // Only the last '=' separates the issue key
+        let rule: MeetingRule = "description:https://meet.company.com/?id=1=ABC-1"
+            .parse()
+            .unwrap();
+        assert_eq!(rule.regex.as_str(), "https://meet.company.com/?id=1");
+        assert_eq!(rule.issue_key, "ABC-1");
+    }
+
+    #[test]
+    fn rejects_the_invalid_meeting_rules() {
+        for rule in [
+            "title=ABC-1",
+            "title:sync",
+            "title:=ABC-1",
+            "title:sync=",
+            "room:sync=ABC-1",
+            "title:(sync=ABC-1",
+        ] {
+            assert!(rule.parse::<MeetingRule>().is_err(), "{}", rule);
+        }
+    }
+}
@@ -1,175 +1,160 @@
+use crate::calendar::calendar_source::CalendarSource;
+use crate::calendar::meeting_rule::MeetingRule;
 use crate::calendar::models::meeting::Meeting;
+use crate::calendar::overlaps::{truncate_overlaps, OverlapPreferences};
+use crate::calendar::skipped_events::{SkippedEventKind, SkippedEvents};
+use crate::calendar::timezones::{
+    param_value, parse_ical_naive_date_time, property_value, CalendarTimeZones, IcalZone,
+};
+use crate::calendar::working_hours::WorkingHours;
 use crate::work_event::{WorkEvent, WorkEvents};
 use crate::ToWorkEvents;
-use chrono::{NaiveDate, NaiveDateTime, TimeZone};
-use chrono_tz::UTC;
-use ical::parser::ical::component::IcalCalendar;
+use chrono::{Duration, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::{Tz, UTC};
+use ical::parser::ical::component::{IcalCalendar, IcalEvent};
 use ical::property::Property;
+use log::{info, warn};
 use num_traits::ToPrimitive;
 use regex::Regex;
 use rrule::RRuleSet;
+use std::cmp::{max, min};
+use std::sync::LazyLock;
+
+// e.g. "PT1H30M", with the weeks, days, hours, minutes and seconds captured
+static DURATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\+?P(?:(\d+)W)?(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)S)?)?$").unwrap()
+});
+
+/// How the events of a calendar are turned into meetings
+#[derive(Debug, Clone)]
+pub struct MeetingsOptions {
+    // Local time zone of the user
+    pub timezone: Tz,
+    // Events spanning several days are clipped to these hours
+    pub working_hours: WorkingHours,
+    // Email of the user, to find their participation status in the attendees
+    pub user_email: String,
+    pub skipped_events: SkippedEvents,
+    // Which meeting owns the time where several meetings overlap
+    pub overlap_preferences: OverlapPreferences,
+    // Issue keys of the meetings without any issue key in their title or description
+    pub rules: Vec<MeetingRule>,
+}
 
 pub trait FromIcal<T> {
     fn from_icalendar(
-        calendar: &IcalCalendar,
+        calendars: &[(CalendarSource, IcalCalendar)],
         date: &NaiveDate,
         project_prefixes: &[String],
         remove_overlaps: bool,
+        options: &MeetingsOptions,
     ) -> T;
 }
 
 impl FromIcal<Vec<Meeting>> for Vec<Meeting> {
     fn from_icalendar(
-        calendar: &IcalCalendar,
+        calendars: &[(CalendarSource, IcalCalendar)],
         date: &NaiveDate,
         project_prefixes: &[String],
         remove_overlaps: bool,
+        options: &MeetingsOptions,
     ) -> Vec<Meeting> {
         let issues_regexes: Vec<Regex> = project_prefixes
             .iter()
             .map(|prefix| Regex::new(format!("{}-[0-9]+", prefix).as_str()).unwrap())
             .collect();
 
-        let mut meetings: Vec<Meeting> = calendar
-            .events
+        // The meetings of all the calendars are merged before removing the overlaps
+        let meetings: Vec<Meeting> = calendars
             .iter()
-            .map(|event| {
-                let mut meeting = Meeting::new_default();
-
-                // These fields are used for computing the recurrence rule (if any)
-                let mut has_rrule = false;
-                // "rrule" properties are RRULE, RDATE, EXRULE, EXDATE and DTSTART
-                let mut rrule_properties: Vec<String> = Vec::new();
-
-                // Parse event properties
-                for property in event.properties.iter() {
-                    match property.name.as_str() {
-                        "SUMMARY" => meeting.title = property.value.as_ref().unwrap().clone(),
-                        "DESCRIPTION" => {
-                            meeting.description = property.value.as_ref().unwrap().clone()
-                        }
-                        "DTEND" => meeting.end_time = parse_ical_date_time(property),
-                        "DTSTART" | "RRULE" | "RDATE" | "EXRULE" | "EXDATE" => {
-                            rrule_properties.push(format!(
-                                "{}:{}",
-                                property.name.clone(),
-                                property.value.as_ref().unwrap().clone()
-                            ));
-
-                            match property.name.as_str() {
-                                "DTSTART" => meeting.start_time = parse_ical_date_time(property),
-                                "RRULE" => has_rrule = true,
-                                _ => {}
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-
-                // Abort early for meetings not on the specific date
-                if (!has_rrule && meeting.start_time.date() != *date)
-                    || (has_rrule && !check_rrule_on_date(&rrule_properties, date))
-                {
-                    return None;
-                }
-
-                // Parse tempo code from title, and fallback on description
-                meeting.tempo_code = extract_tempo_code(meeting.title.as_str(), &issues_regexes)
-                    .or_else(|| extract_tempo_code(meeting.description.as_str(), &issues_regexes));
-
-                // Filter out meetings with no duration
-                if !meeting.has_start_time() || !meeting.has_end_time() {
-                    return None;
-                }
-
-                // Compute meeting duration
-                meeting.duration = (meeting.end_time - meeting.start_time)
-                    .num_seconds()
-                    .to_i32()
-                    .unwrap();
-
-                Some(meeting)
+            .flat_map(|(source, calendar)| {
+                calendar_meetings(calendar, source, date, &issues_regexes, options)
             })
-            .flatten()
             .collect();
 
         if !remove_overlaps {
             return meetings;
         }
 
-        // Remove overlapping and duplicates meetings
-        let mut filtered_meetings: Vec<Meeting> = Vec::new();
-        while !meetings.is_empty() {
-            let meeting1 = meetings.swap_remove(0);
+        truncate_overlaps(meetings, &options.overlap_preferences)
+    }
+}
 
-            if meetings.is_empty() {
-                // Adds the last element
-                filtered_meetings.push(meeting1);
-                break;
-            }
+fn calendar_meetings(
+    calendar: &IcalCalendar,
+    source: &CalendarSource,
+    date: &NaiveDate,
+    issues_regexes: &[Regex],
+    options: &MeetingsOptions,
+) -> Vec<Meeting> {
+    let timezones = CalendarTimeZones::new(calendar, options.timezone);
 
-            // TODO: improve meetings parsing by truncating overlapping meetings, instead of
-            //       ignoring them
-            let mut i = 0;
-            while i < meetings.len() {
-                let meeting2 = meetings.get(i).unwrap();
-
-                // meeting1 starts before and ends after meeting2
-                // -> keep meeting1
-                if meeting1.start_time <= meeting2.start_time
-                    && meeting1.end_time >= meeting2.end_time
-                {
-                    filtered_meetings.push(meeting1);
-                    meetings.swap_remove(i);
-                    break;
-                }
+    let events: Vec<CalendarEvent> = calendar
+        .events
+        .iter()
+        .filter_map(|event| parse_event(event, &timezones, &options.user_email))
+        .collect();
 
-                // meeting1 starts before and ends during meeting2
-                // -> keep meeting1 if longer
-                if meeting1.start_time < meeting2.start_time
-                    && meeting1.end_time > meeting2.start_time
-                    && meeting1.duration > meeting2.duration
-                {
-                    filtered_meetings.push(meeting1);
-                    meetings.swap_remove(i);
-                    break;
-                }
+    // Occurrences of recurring events that were moved or cancelled, and replaced by another event
+    let overridden_occurrences: Vec<(&String, &NaiveDateTime)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.as_ref()?, event.recurrence_id.as_ref()?)))
+        .collect();
 
-                // meeting1 starts during and ends after meeting2
-                // -> keep meeting1 if longer
-                if meeting1.start_time > meeting2.start_time
-                    && meeting1.start_time < meeting2.end_time
-                    && meeting1.end_time > meeting2.end_time
-                    && meeting1.duration > meeting2.duration
-                {
-                    filtered_meetings.push(meeting1);
-                    meetings.swap_remove(i);
-                    break;
+    events
+        .iter()
+        .filter(|event| !event.cancelled)
+        .flat_map(|event| {
+            meetings_on_date(
+                event,
+                date,
+                &timezones,
+                &options.working_hours,
+                &overridden_occurrences,
+            )
+            .into_iter()
+            .map(move |meeting| (event, meeting))
+        })
+        .filter(
+            |(event, meeting)| match options.skipped_events.find(&event.kinds) {
+                Some(kind) => {
+                    info!("Skipped meeting '{}' ({})", meeting.title, kind);
+                    false
                 }
-
-                // meeting1 starts during and ends during meeting2
-                // -> skip meeting1
-                if meeting1.start_time >= meeting2.start_time
-                    && meeting1.end_time <= meeting2.end_time
-                {
-                    break;
+                None if !source.accepts(meeting) => {
+                    info!(
+                        "Skipped meeting '{}' (filtered out of {})",
+                        meeting.title, source.location
+                    );
+                    false
                 }
+                None => true,
+            },
+        )
+        .map(|(_, mut meeting)| {
+            // Parse tempo code from title, and fallback on description
+            meeting.tempo_code = extract_tempo_code(meeting.title.as_str(), issues_regexes)
+                .or_else(|| extract_tempo_code(meeting.description.as_str(), issues_regexes))
+                // Then use the first matching rule
+                .or_else(|| {
+                    options
+                        .rules
+                        .iter()
+                        .find(|rule| rule.matches(&meeting))
+                        .map(|rule| rule.issue_key.to_string())
+                })
+                // Then the default issue of the calendar
+                .or_else(|| source.default_issue.clone());
 
-                // In any other case, meeting1 and meeting2 doesn't overlap
-                // -> check other meetings
-                i += 1;
+            // Compute meeting duration
+            meeting.duration = (meeting.end_time - meeting.start_time)
+                .num_seconds()
+                .to_i32()
+                .unwrap();
 
-                if i == meetings.len() {
-                    // meeting1 did not overlap with any other meeting
-                    // -> keep it
-                    filtered_meetings.push(meeting1);
-                    break;
-                }
-            }
-        }
-
-        filtered_meetings
-    }
+            meeting
+        })
+        .collect()
 }
 
 impl ToWorkEvents<Meeting> for Vec<Meeting> {
@@ -200,25 +185,332 @@ impl ToWorkEvents<Meeting> for Vec<Meeting> {
     }
 }
 
-// TODO: timezone support (the TZ is currently ignored)
-fn parse_ical_date_time(property: &Property) -> NaiveDateTime {
-    let str_date_time = property.value.as_ref().unwrap().clone();
-    return NaiveDateTime::parse_from_str(str_date_time.as_str(), "%Y%m%dT%H%M%S").unwrap();
+// A VEVENT of the calendar, before being matched with the date
+struct CalendarEvent<'a> {
+    uid: Option<String>,
+    // Original start (in the local time zone) of the occurrence replaced by this event, for the moved or cancelled
+    // occurrences of a recurring event
+    recurrence_id: Option<NaiveDateTime>,
+    cancelled: bool,
+    // Why the event could be skipped (declined, free, ...)
+    kinds: Vec<SkippedEventKind>,
+    has_rrule: bool,
+    start_zone: IcalZone,
+    // "rrule" properties are RRULE, RDATE, EXRULE, EXDATE and DTSTART
+    rrule_properties: Vec<&'a Property>,
+    meeting: Meeting,
+}
+
+/// Parses an event with a start and an end time. All-day events are not meetings (but can be absences).
+fn parse_event<'a>(
+    event: &'a IcalEvent,
+    timezones: &CalendarTimeZones,
+    user_email: &str,
+) -> Option<CalendarEvent<'a>> {
+    if event
+        .properties
+        .iter()
+        .any(|property| property.name == "DTSTART" && parse_ical_date(property).is_some())
+    {
+        return None;
+    }
+
+    let mut calendar_event = CalendarEvent {
+        uid: None,
+        recurrence_id: None,
+        cancelled: false,
+        kinds: Vec::new(),
+        has_rrule: false,
+        start_zone: IcalZone::Floating,
+        rrule_properties: Vec::new(),
+        meeting: Meeting::new_default(),
+    };
+    let meeting = &mut calendar_event.meeting;
+    // Used when there is no DTEND
+    let mut duration: Option<Duration> = None;
+
+    // Parse event properties
+    for property in event.properties.iter() {
+        match property.name.as_str() {
+            "UID" => calendar_event.uid = property.value.clone(),
+            "RECURRENCE-ID" => {
+                calendar_event.recurrence_id = timezones.parse_local_date_time(property)
+            }
+            "STATUS" => match property.value.as_deref() {
+                Some("CANCELLED") => calendar_event.cancelled = true,
+                Some("TENTATIVE") => calendar_event.kinds.push(SkippedEventKind::Tentative),
+                _ => {}
+            },
+            "ORGANIZER" => {
+                meeting.organizer = mail_address(property);
+                meeting.organized_by_user = is_user_address(property, user_email);
+            }
+            "ATTENDEE" => {
+                meeting.attendees.extend(mail_address(property));
+
+                if is_user_address(property, user_email) {
+                    match param_value(property, "PARTSTAT").as_deref() {
+                        Some("DECLINED") => calendar_event.kinds.push(SkippedEventKind::Declined),
+                        Some("TENTATIVE") => calendar_event.kinds.push(SkippedEventKind::Tentative),
+                        Some("NEEDS-ACTION") => {
+                            calendar_event.kinds.push(SkippedEventKind::NeedsAction)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "LOCATION" => meeting.location = property.value.clone().unwrap_or_default(),
+            "CATEGORIES" => meeting.categories.extend(
+                property
+                    .value
+                    .iter()
+                    .flat_map(|value| value.split(','))
+                    .map(|category| category.trim().to_string()),
+            ),
+            "TRANSP" if property.value.as_deref() == Some("TRANSPARENT") => {
+                calendar_event.kinds.push(SkippedEventKind::Free)
+            }
+            "X-MICROSOFT-CDO-BUSYSTATUS" => match property.value.as_deref() {
+                Some("FREE") => calendar_event.kinds.push(SkippedEventKind::Free),
+                Some("TENTATIVE") => calendar_event.kinds.push(SkippedEventKind::Tentative),
+                _ => {}
+            },
+            "CLASS" if matches!(property.value.as_deref(), Some("PRIVATE" | "CONFIDENTIAL")) => {
+                calendar_event.kinds.push(SkippedEventKind::Private)
+            }
+            "SUMMARY" => meeting.title = property.value.clone().unwrap_or_default(),
+            "DESCRIPTION" => meeting.description = property.value.clone().unwrap_or_default(),
+            "DTEND" => match timezones.parse_local_date_time(property) {
+                Some(end_time) => meeting.end_time = end_time,
+                None => return invalid_event(event, property),
+            },
+            "DURATION" => match property.value.as_deref().and_then(parse_ical_duration) {
+                Some(value) => duration = Some(value),
+                None => return invalid_event(event, property),
+            },
+            "DTSTART" | "RRULE" | "RDATE" | "EXRULE" | "EXDATE" => {
+                calendar_event.rrule_properties.push(property);
+
+                match property.name.as_str() {
+                    "DTSTART" => {
+                        meeting.start_time = match timezones.parse_local_date_time(property) {
+                            Some(start_time) => start_time,
+                            None => return invalid_event(event, property),
+                        };
+                        calendar_event.start_zone = timezones.zone_of(property);
+                    }
+                    "RRULE" => calendar_event.has_rrule = true,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !meeting.has_end_time() {
+        if let Some(duration) = duration {
+            meeting.end_time = meeting.start_time + duration;
+        }
+    }
+
+    // Filter out meetings with no duration
+    if !meeting.has_start_time() || !meeting.has_end_time() {
+        return None;
+    }
+
+    Some(calendar_event)
+}
+
+/// Occurrences of the event on the date (at most one for non-recurring events)
+fn meetings_on_date(
+    event: &CalendarEvent,
+    date: &NaiveDate,
+    timezones: &CalendarTimeZones,
+    working_hours: &WorkingHours,
+    overridden_occurrences: &[(&String, &NaiveDateTime)],
+) -> Vec<Meeting> {
+    if event.has_rrule && event.recurrence_id.is_none() {
+        // Each occurrence has the duration of the first one
+        let duration = event.meeting.end_time - event.meeting.start_time;
+        // Occurrences started on the previous days can still be running on the date
+        let first_date = *date - Duration::days(duration.num_days() + 1);
+        rrule_occurrences_between(event, &first_date, date, timezones)
+            .into_iter()
+            .filter(|occurrence| {
+                !overridden_occurrences.iter().any(|(uid, recurrence_id)| {
+                    Some(*uid) == event.uid.as_ref() && *recurrence_id == occurrence
+                })
+            })
+            .filter_map(|occurrence| {
+                clip_to_date(
+                    Meeting {
+                        start_time: occurrence,
+                        end_time: occurrence + duration,
+                        ..event.meeting.clone()
+                    },
+                    date,
+                    working_hours,
+                )
+            })
+            .collect()
+    } else {
+        clip_to_date(event.meeting.clone(), date, working_hours)
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The part of the meeting on the date, if any.
+/// Events crossing midnight are clipped to the date, then to the working hours of the day when they overlap them:
+/// a workshop or a trip is logged within the working hours, and a late call outside of them is kept as is.
+fn clip_to_date(
+    mut meeting: Meeting,
+    date: &NaiveDate,
+    working_hours: &WorkingHours,
+) -> Option<Meeting> {
+    if meeting.end_time > meeting.start_time.date().succ().and_hms(0, 0, 0) {
+        meeting.start_time = max(meeting.start_time, date.and_hms(0, 0, 0));
+        meeting.end_time = min(meeting.end_time, date.succ().and_hms(0, 0, 0));
+
+        let start_time = max(meeting.start_time, working_hours.start_on(date));
+        let end_time = min(meeting.end_time, working_hours.end_on(date));
+        if start_time < end_time {
+            meeting.start_time = start_time;
+            meeting.end_time = end_time;
+        }
+
+        if meeting.start_time >= meeting.end_time {
+            return None;
+        }
+    }
+
+    // Abort early for meetings not on the specific date
+    if meeting.start_time.date() != *date {
+        return None;
+    }
+
+    Some(meeting)
 }
 
-fn check_rrule_on_date(recurrence_rules: &[String], date: &NaiveDate) -> bool {
-    let rrule_str = recurrence_rules.join("\n");
-    let rrule_set: RRuleSet = rrule_str.parse().unwrap();
+// e.g. "mailto:john.doe@company.com"
+pub fn mail_address(property: &Property) -> Option<String> {
+    let address = property.value.as_ref()?.trim();
+    let address = address
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .map(|_| &address[7..])
+        .unwrap_or(address);
+
+    Some(address.to_lowercase())
+}
 
-    let recurrences_on_date = rrule_set.between(
-        UTC.from_local_datetime(&date.and_hms(0, 0, 0)).unwrap(),
-        UTC.from_local_datetime(&date.and_hms(23, 59, 59)).unwrap(),
+fn is_user_address(property: &Property, user_email: &str) -> bool {
+    mail_address(property)
+        .map(|address| address.eq_ignore_ascii_case(user_email))
+        .unwrap_or(false)
+}
+
+/// Parses a date-only value (i.e. "VALUE=DATE"), as used by all-day events
+pub fn parse_ical_date(property: &Property) -> Option<NaiveDate> {
+    let str_date = property.value.as_ref()?;
+    if str_date.len() != 8 {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(str_date.as_str(), "%Y%m%d").ok()
+}
+
+/// Parses a duration value (e.g. "PT1H30M", "P1D" or "P2W"). Negative durations are not supported.
+pub fn parse_ical_duration(value: &str) -> Option<Duration> {
+    let captures = DURATION_REGEX.captures(value.trim())?;
+    let number = |index: usize| {
+        captures
+            .get(index)
+            .map(|m| m.as_str().parse::<i64>().unwrap())
+            .unwrap_or(0)
+    };
+
+    Some(
+        Duration::weeks(number(1))
+            + Duration::days(number(2))
+            + Duration::hours(number(3))
+            + Duration::minutes(number(4))
+            + Duration::seconds(number(5)),
+    )
+}
+
+/// Start times (in the local time zone) of the occurrences between the dates (included).
+/// The recurrences are computed with the wall-clock times of the DTSTART zone (so they follow its DST changes),
+/// and then converted to the local time zone
+fn rrule_occurrences_between(
+    event: &CalendarEvent,
+    first_date: &NaiveDate,
+    last_date: &NaiveDate,
+    timezones: &CalendarTimeZones,
+) -> Vec<NaiveDateTime> {
+    let start_zone = &event.start_zone;
+    let rrule_str = event
+        .rrule_properties
+        .iter()
+        .map(|property| {
+            let value = property.value.as_deref().unwrap_or_default();
+            match property.name.as_str() {
+                "DTSTART" | "RDATE" | "EXDATE" => {
+                    let zone = timezones.zone_of(property);
+                    let values: Vec<String> = value
+                        .split(',')
+                        .map(|value| match parse_ical_naive_date_time(value) {
+                            Some(date_time) => timezones
+                                .utc_to_zone(&timezones.zone_to_utc(&date_time, &zone), start_zone)
+                                .format("%Y%m%dT%H%M%S")
+                                .to_string(),
+                            // Date-only values
+                            None => value.to_string(),
+                        })
+                        .collect();
+                    format!("{}:{}", property.name, values.join(","))
+                }
+                _ => format!("{}:{}", property.name, value),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let rrule_set: RRuleSet = match rrule_str.parse() {
+        Ok(rrule_set) => rrule_set,
+        Err(error) => {
+            warn!(
+                "Skipped the recurring event {}: invalid recurrence ({})",
+                event.uid.as_deref().unwrap_or("(without UID)"),
+                error
+            );
+            return Vec::new();
+        }
+    };
+
+    // The local dates can be the day before or after in the DTSTART zone
+    let recurrences_around_dates = rrule_set.between(
+        UTC.from_local_datetime(&(*first_date - Duration::days(1)).and_hms(0, 0, 0))
+            .unwrap(),
+        UTC.from_local_datetime(&(*last_date + Duration::days(1)).and_hms(23, 59, 59))
+            .unwrap(),
         true,
     );
 
-    return recurrences_on_date
+    recurrences_around_dates
         .iter()
-        .any(|rec| rec.naive_utc().date() == *date);
+        .map(|rec| timezones.to_local(&rec.naive_utc(), start_zone))
+        .filter(|occurrence| *first_date <= occurrence.date() && occurrence.date() <= *last_date)
+        .collect()
+}
+
+fn invalid_event<T>(event: &IcalEvent, property: &Property) -> Option<T> {
+    warn!(
+        "Skipped the invalid event {}: invalid {} ({})",
+        property_value(&event.properties, "UID").unwrap_or_else(|| "(without UID)".to_string()),
+        property.name,
+        property.value.as_deref().unwrap_or_default()
+    );
+    None
 }
 
 fn extract_tempo_code(text: &str, issues_regexes: &[Regex]) -> Option<String> {
@@ -229,3 +521,24 @@ fn extract_tempo_code(text: &str, issues_regexes: &[Regex]) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_ical_durations() {
+        assert_eq!(parse_ical_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_ical_duration("+PT45S"), Some(Duration::seconds(45)));
+        assert_eq!(parse_ical_duration(" P1D "), Some(Duration::days(1)));
+        assert_eq!(parse_ical_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_ical_duration("P2W"), Some(Duration::weeks(2)));
+    }
+
+    #[test]
+    fn rejects_the_negative_and_invalid_ical_durations() {
+        for value in ["-PT15M", "PT1.5H", "1H", "PT1H30", "P1H", ""] {
+            assert_eq!(parse_ical_duration(value), None, "{}", value);
+        }
+    }
+}
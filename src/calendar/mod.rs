@@ -1,3 +1,14 @@
+pub mod absences;
+pub mod caldav;
+pub mod calendar_cache;
 pub mod calendar_parser;
+pub mod calendar_source;
+pub mod meeting_durations;
+pub mod meeting_rule;
 pub mod meetings;
 pub mod models;
+pub mod overlaps;
+pub mod overtime;
+pub mod skipped_events;
+pub mod timezones;
+pub mod working_hours;
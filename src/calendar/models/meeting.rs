@@ -1,13 +1,19 @@
 use chrono::NaiveDateTime;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Meeting {
     pub title: String,
     pub description: String,
+    pub location: String,
+    // Email addresses
+    pub organizer: Option<String>,
+    pub attendees: Vec<String>,
+    pub categories: Vec<String>,
     pub tempo_code: Option<String>,
     pub start_time: NaiveDateTime,
     pub end_time: NaiveDateTime,
     pub duration: i32,
+    pub organized_by_user: bool,
 }
 
 impl Meeting {
@@ -15,10 +21,15 @@ impl Meeting {
         Self {
             title: "".to_string(),
             description: "".to_string(),
+            location: "".to_string(),
+            organizer: None,
+            attendees: Vec::new(),
+            categories: Vec::new(),
             tempo_code: None,
             start_time: NaiveDateTime::from_timestamp(0, 0),
             end_time: NaiveDateTime::from_timestamp(0, 0),
             duration: 0,
+            organized_by_user: false,
         }
     }
 
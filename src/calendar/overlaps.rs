@@ -0,0 +1,288 @@
+use crate::calendar::models::meeting::Meeting;
+use chrono::NaiveDateTime;
+use num_traits::ToPrimitive;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Which meeting owns the time where several meetings overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapPreference {
+    // The meeting that started first
+    Earlier,
+    // The meeting that started last (e.g. when leaving a meeting early for another one)
+    Later,
+    Shorter,
+    Longer,
+    // The meeting with an issue key in its title or description
+    IssueKey,
+    // The meeting organized by the user
+    Organizer,
+}
+
+/// Comma separated preferences, the next ones being used to break the ties of the previous ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlapPreferences {
+    pub preferences: Vec<OverlapPreference>,
+}
+
+impl FromStr for OverlapPreference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "earlier" => Ok(OverlapPreference::Earlier),
+            "later" => Ok(OverlapPreference::Later),
+            "shorter" => Ok(OverlapPreference::Shorter),
+            "longer" => Ok(OverlapPreference::Longer),
+            "issue-key" => Ok(OverlapPreference::IssueKey),
+            "organizer" => Ok(OverlapPreference::Organizer),
+            _ => Err(format!(
+                "Unknown overlap preference '{}'. Expected one of: earlier, later, shorter, longer, issue-key, organizer",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OverlapPreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlapPreference::Earlier => write!(f, "earlier"),
+            OverlapPreference::Later => write!(f, "later"),
+            OverlapPreference::Shorter => write!(f, "shorter"),
+            OverlapPreference::Longer => write!(f, "longer"),
+            OverlapPreference::IssueKey => write!(f, "issue-key"),
+            OverlapPreference::Organizer => write!(f, "organizer"),
+        }
+    }
+}
+
+impl FromStr for OverlapPreferences {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            preferences: s
+                .split(',')
+                .map(|preference| preference.parse())
+                .collect::<Result<Vec<OverlapPreference>, String>>()?,
+        })
+    }
+}
+
+impl fmt::Display for OverlapPreferences {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.preferences
+                .iter()
+                .map(|preference| preference.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        )
+    }
+}
+
+impl Default for OverlapPreferences {
+    fn default() -> Self {
+        Self {
+            preferences: vec![OverlapPreference::Longer, OverlapPreference::Earlier],
+        }
+    }
+}
+
+impl OverlapPreferences {
+    /// `Ordering::Greater` if the first meeting is preferred
+    pub fn compare(&self, meeting1: &Meeting, meeting2: &Meeting) -> Ordering {
+        self.preferences
+            .iter()
+            .map(|preference| match preference {
+                OverlapPreference::Earlier => meeting2.start_time.cmp(&meeting1.start_time),
+                OverlapPreference::Later => meeting1.start_time.cmp(&meeting2.start_time),
+                OverlapPreference::Shorter => meeting2.duration.cmp(&meeting1.duration),
+                OverlapPreference::Longer => meeting1.duration.cmp(&meeting2.duration),
+                OverlapPreference::IssueKey => meeting1
+                    .tempo_code
+                    .is_some()
+                    .cmp(&meeting2.tempo_code.is_some()),
+                OverlapPreference::Organizer => {
+                    meeting1.organized_by_user.cmp(&meeting2.organized_by_user)
+                }
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Sweeps the meetings boundaries, and gives each slice of time to the preferred meeting among the ones occupying it.
+/// The other meetings are truncated (or split, or dropped if they are entirely covered).
+pub fn truncate_overlaps(meetings: Vec<Meeting>, preferences: &OverlapPreferences) -> Vec<Meeting> {
+    let mut boundaries: Vec<NaiveDateTime> = meetings
+        .iter()
+        .flat_map(|meeting| [meeting.start_time, meeting.end_time])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut parts: Vec<Meeting> = Vec::new();
+    // Index of the meeting owning the last part
+    let mut last_owner: Option<usize> = None;
+
+    for slice in boundaries.windows(2) {
+        let (start, end) = (slice[0], slice[1]);
+
+        // Ties are broken by the order of the meetings
+        let owner = meetings
+            .iter()
+            .enumerate()
+            .filter(|(_, meeting)| meeting.start_time <= start && meeting.end_time >= end)
+            .max_by(|(index1, meeting1), (index2, meeting2)| {
+                preferences
+                    .compare(meeting1, meeting2)
+                    .then(index2.cmp(index1))
+            });
+
+        match owner {
+            Some((index, _)) if last_owner == Some(index) => {
+                parts.last_mut().unwrap().end_time = end;
+            }
+            Some((index, meeting)) => {
+                parts.push(Meeting {
+                    start_time: start,
+                    end_time: end,
+                    ..meeting.clone()
+                });
+                last_owner = Some(index);
+            }
+            None => last_owner = None,
+        }
+    }
+
+    for part in parts.iter_mut() {
+        part.duration = (part.end_time - part.start_time)
+            .num_seconds()
+            .to_i32()
+            .unwrap();
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn meeting(title: &str, start: (u32, u32), end: (u32, u32)) -> Meeting {
+        let date = NaiveDate::from_ymd(2022, 10, 18);
+        let start_time = date.and_hms(start.0, start.1, 0);
+        let end_time = date.and_hms(end.0, end.1, 0);
+        Meeting {
+            title: title.to_string(),
+            start_time,
+            end_time,
+            duration: (end_time - start_time).num_seconds().to_i32().unwrap(),
+            ..Meeting::new_default()
+        }
+    }
+
+    fn parts(meetings: Vec<Meeting>, preferences: &str) -> Vec<(String, String, String)> {
+        truncate_overlaps(meetings, &preferences.parse().unwrap())
+            .into_iter()
+            .map(|part| {
+                (
+                    part.title,
+                    part.start_time.format("%H:%M").to_string(),
+                    part.end_time.format("%H:%M").to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn part(title: &str, start: &str, end: &str) -> (String, String, String) {
+        (title.to_string(), start.to_string(), end.to_string())
+    }
+
+    #[test]
+    fn parses_the_overlap_preferences() {
+        let preferences: OverlapPreferences = " Issue-Key,organizer , later".parse().unwrap();
+        assert_eq!(
+            preferences.preferences,
+            vec![
+                OverlapPreference::IssueKey,
+                OverlapPreference::Organizer,
+                OverlapPreference::Later
+            ]
+        );
+        assert_eq!(preferences.to_string(), "issue-key,organizer,later");
+        assert_eq!(OverlapPreferences::default().to_string(), "longer,earlier");
+
+        assert!("shorter,first".parse::<OverlapPreferences>().is_err());
+        assert!("".parse::<OverlapPreferences>().is_err());
+    }
+
+    #[test]
+    fn splits_the_longer_meeting_around_a_shorter_one() {
+        let meetings = vec![
+            meeting("Workshop", (9, 0), (12, 0)),
+            meeting("Standup", (10, 0), (10, 30)),
+        ];
+
+        assert_eq!(
+            parts(meetings.clone(), "shorter"),
+            vec![
+                part("Workshop", "09:00", "10:00"),
+                part("Standup", "10:00", "10:30"),
+                part("Workshop", "10:30", "12:00"),
+            ]
+        );
+        // The covered meeting is dropped
+        assert_eq!(
+            parts(meetings, "longer"),
+            vec![part("Workshop", "09:00", "12:00")]
+        );
+    }
+
+    #[test]
+    fn truncates_the_meetings_overlapping_partially() {
+        let meetings = vec![
+            meeting("Review", (14, 0), (15, 0)),
+            meeting("Planning", (14, 30), (16, 0)),
+        ];
+
+        assert_eq!(
+            parts(meetings.clone(), "earlier"),
+            vec![
+                part("Review", "14:00", "15:00"),
+                part("Planning", "15:00", "16:00"),
+            ]
+        );
+        assert_eq!(
+            parts(meetings, "later"),
+            vec![
+                part("Review", "14:00", "14:30"),
+                part("Planning", "14:30", "16:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_first_of_the_equivalent_meetings() {
+        let meetings = vec![
+            meeting("Sync", (9, 0), (10, 0)),
+            meeting("Sync (copy)", (9, 0), (10, 0)),
+            // Adjacent meetings are not overlapping
+            meeting("Lunch", (10, 0), (11, 0)),
+        ];
+
+        assert_eq!(
+            parts(meetings, "longer,earlier"),
+            vec![
+                part("Sync", "09:00", "10:00"),
+                part("Lunch", "10:00", "11:00")
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::calendar::models::meeting::Meeting;
+use crate::calendar::working_hours::WorkingHours;
+use crate::utils::date::format_duration;
+use crate::work_event::WorkEvents;
+use chrono::NaiveDate;
+use log::info;
+use num_traits::ToPrimitive;
+use std::cmp::{max, min};
+use std::fmt;
+use std::str::FromStr;
+
+/// What to do with the meetings exceeding the remaining time of the day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeetingOvertime {
+    // Every meeting is logged, and the time beyond the day is reported as overtime
+    #[default]
+    Log,
+    // The meetings are logged in chronological order, until the day is full
+    Cap,
+    // Only the parts of the meetings within the working hours are logged (and the overtime reported, if any)
+    WorkingHours,
+}
+
+impl FromStr for MeetingOvertime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "log" => Ok(MeetingOvertime::Log),
+            "cap" => Ok(MeetingOvertime::Cap),
+            "working-hours" => Ok(MeetingOvertime::WorkingHours),
+            _ => Err(format!(
+                "Unknown meeting overtime policy '{}'. Expected one of: log, cap, working-hours",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MeetingOvertime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeetingOvertime::Log => write!(f, "log"),
+            MeetingOvertime::Cap => write!(f, "cap"),
+            MeetingOvertime::WorkingHours => write!(f, "working-hours"),
+        }
+    }
+}
+
+/// Clips the meetings to the working hours of the date, and removes the ones entirely outside of them
+pub fn clip_to_working_hours(
+    meetings: Vec<Meeting>,
+    working_hours: &WorkingHours,
+    date: &NaiveDate,
+) -> Vec<Meeting> {
+    meetings
+        .into_iter()
+        .filter_map(|mut meeting| {
+            meeting.start_time = max(meeting.start_time, working_hours.start_on(date));
+            meeting.end_time = min(meeting.end_time, working_hours.end_on(date));
+            if meeting.start_time >= meeting.end_time {
+                info!(
+                    "Skipped meeting '{}' (outside working hours)",
+                    meeting.title
+                );
+                return None;
+            }
+
+            meeting.duration = (meeting.end_time - meeting.start_time)
+                .num_seconds()
+                .to_i32()
+                .unwrap();
+            Some(meeting)
+        })
+        .collect()
+}
+
+/// Shortens (or removes) the latest meetings, so they don't exceed the remaining time
+pub fn cap_to_remaining_time(
+    mut events: WorkEvents<Meeting>,
+    remaining_time: i32,
+) -> WorkEvents<Meeting> {
+    events.sort_by_key(|event| event.event.start_time);
+
+    let mut available_time = max(remaining_time, 0);
+    events.retain_mut(|event| {
+        if event.duration > available_time {
+            info!(
+                "Meeting '{}' capped from {} to {} (day already full)",
+                event.event.title,
+                format_duration(&event.duration),
+                format_duration(&available_time)
+            );
+            event.duration = available_time;
+        }
+        available_time -= event.duration;
+        event.duration > 0
+    });
+
+    events.sort_by_key(|event| std::cmp::Reverse(event.duration));
+    events
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Kinds of calendar events that are not logged as meetings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkippedEventKind {
+    // Declined by the user
+    Declined,
+    // Tentatively accepted by the user, or not confirmed by the organizer
+    Tentative,
+    // Not answered by the user yet
+    NeedsAction,
+    // Not blocking time in the calendar, e.g. a focus time block
+    Free,
+    // Private or confidential events
+    Private,
+}
+
+/// Comma separated kinds, e.g. "declined,free", or "none" to log every event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedEvents {
+    pub kinds: Vec<SkippedEventKind>,
+}
+
+impl fmt::Display for SkippedEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkippedEventKind::Declined => write!(f, "declined"),
+            SkippedEventKind::Tentative => write!(f, "tentative"),
+            SkippedEventKind::NeedsAction => write!(f, "needs-action"),
+            SkippedEventKind::Free => write!(f, "free"),
+            SkippedEventKind::Private => write!(f, "private"),
+        }
+    }
+}
+
+impl FromStr for SkippedEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "declined" => Ok(SkippedEventKind::Declined),
+            "tentative" => Ok(SkippedEventKind::Tentative),
+            "needs-action" => Ok(SkippedEventKind::NeedsAction),
+            "free" => Ok(SkippedEventKind::Free),
+            "private" => Ok(SkippedEventKind::Private),
+            _ => Err(format!(
+                "Unknown event kind '{}'. Expected one of: declined, tentative, needs-action, free, private",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for SkippedEvents {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("none") {
+            return Ok(Self { kinds: Vec::new() });
+        }
+
+        Ok(Self {
+            kinds: s
+                .split(',')
+                .map(|kind| kind.parse())
+                .collect::<Result<Vec<SkippedEventKind>, String>>()?,
+        })
+    }
+}
+
+impl fmt::Display for SkippedEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kinds.is_empty() {
+            return write!(f, "none");
+        }
+
+        write!(
+            f,
+            "{}",
+            self.kinds
+                .iter()
+                .map(|kind| kind.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        )
+    }
+}
+
+impl Default for SkippedEvents {
+    fn default() -> Self {
+        Self {
+            kinds: vec![SkippedEventKind::Declined, SkippedEventKind::Free],
+        }
+    }
+}
+
+impl SkippedEvents {
+    /// Returns the first skipped kind among the kinds of an event
+    pub fn find(&self, event_kinds: &[SkippedEventKind]) -> Option<SkippedEventKind> {
+        event_kinds
+            .iter()
+            .find(|kind| self.kinds.contains(kind))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_skipped_events() {
+        assert_eq!(SkippedEvents::default().to_string(), "declined,free");
+        assert_eq!(
+            " Tentative,private"
+                .parse::<SkippedEvents>()
+                .unwrap()
+                .to_string(),
+            "tentative,private"
+        );
+        assert_eq!("none".parse::<SkippedEvents>().unwrap().to_string(), "none");
+        assert!("declined,busy".parse::<SkippedEvents>().is_err());
+    }
+}
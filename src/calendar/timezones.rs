@@ -0,0 +1,344 @@
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::{Tz, UTC};
+use ical::parser::ical::component::{IcalCalendar, IcalTimeZone};
+use ical::property::Property;
+use log::warn;
+use rrule::RRuleSet;
+use std::collections::HashMap;
+
+/// Time zone of a date-time value of the calendar
+#[derive(Debug, Clone)]
+pub enum IcalZone {
+    // "Z" suffix
+    Utc,
+    // No time zone: the value is already in the local time zone
+    Floating,
+    Named(Tz),
+    // A VTIMEZONE of the calendar, unknown to the tz database (e.g. "Customized Time Zone" from Outlook)
+    Custom(String),
+}
+
+/// Converts the date-times of a calendar into the local time zone
+#[derive(Debug)]
+pub struct CalendarTimeZones {
+    local: Tz,
+    custom: HashMap<String, CustomTimeZone>,
+}
+
+#[derive(Debug)]
+struct CustomTimeZone {
+    transitions: Vec<Transition>,
+}
+
+// A STANDARD or DAYLIGHT component of a VTIMEZONE
+#[derive(Debug)]
+struct Transition {
+    // "DTSTART" and "RRULE" lines, to compute the onsets of the transition
+    rrule_lines: Vec<String>,
+    first_onset: NaiveDateTime,
+    offset_to: Duration,
+}
+
+impl CalendarTimeZones {
+    pub fn new(calendar: &IcalCalendar, local: Tz) -> Self {
+        let custom = calendar
+            .timezones
+            .iter()
+            .filter_map(|timezone| {
+                let tz_id = property_value(&timezone.properties, "TZID")?;
+                if resolve_zone_name(&tz_id).is_some() {
+                    return None;
+                }
+                Some((tz_id, CustomTimeZone::new(timezone)))
+            })
+            .collect();
+
+        Self { local, custom }
+    }
+
+    /// Time zone of a date-time property, from its "Z" suffix or its TZID parameter
+    pub fn zone_of(&self, property: &Property) -> IcalZone {
+        if property
+            .value
+            .as_ref()
+            .map(|value| value.ends_with('Z'))
+            .unwrap_or(false)
+        {
+            return IcalZone::Utc;
+        }
+
+        match param_value(property, "TZID") {
+            Some(tz_id) => match resolve_zone_name(&tz_id) {
+                Some(tz) => IcalZone::Named(tz),
+                None if self.custom.contains_key(&tz_id) => IcalZone::Custom(tz_id),
+                None => {
+                    warn!("Unknown time zone '{}', using the local time zone", tz_id);
+                    IcalZone::Floating
+                }
+            },
+            None => IcalZone::Floating,
+        }
+    }
+
+    /// Parses a date-time property (e.g. "20221018T090000Z" or "TZID=Europe/Paris:20221018T090000") into the
+    /// local time zone. Date-only values are not date-times, and return None.
+    pub fn parse_local_date_time(&self, property: &Property) -> Option<NaiveDateTime> {
+        let value = property.value.as_ref()?;
+        let date_time = parse_ical_naive_date_time(value)?;
+        Some(self.to_local(&date_time, &self.zone_of(property)))
+    }
+
+    /// Converts a wall-clock time of the zone to the local time zone
+    pub fn to_local(&self, date_time: &NaiveDateTime, zone: &IcalZone) -> NaiveDateTime {
+        match zone {
+            IcalZone::Floating => *date_time,
+            _ => self
+                .zone_to_utc(date_time, zone)
+                .with_timezone(&self.local)
+                .naive_local(),
+        }
+    }
+
+    pub fn zone_to_utc(&self, date_time: &NaiveDateTime, zone: &IcalZone) -> DateTime<Utc> {
+        match zone {
+            IcalZone::Utc => Utc.from_utc_datetime(date_time),
+            IcalZone::Floating => local_to_utc(&self.local, date_time),
+            IcalZone::Named(tz) => local_to_utc(tz, date_time),
+            IcalZone::Custom(tz_id) => {
+                Utc.from_utc_datetime(&(*date_time - self.custom[tz_id].offset_at(date_time)))
+            }
+        }
+    }
+
+    /// Converts an UTC time to a wall-clock time of the zone
+    pub fn utc_to_zone(&self, date_time: &DateTime<Utc>, zone: &IcalZone) -> NaiveDateTime {
+        match zone {
+            IcalZone::Utc => date_time.naive_utc(),
+            IcalZone::Floating => date_time.with_timezone(&self.local).naive_local(),
+            IcalZone::Named(tz) => date_time.with_timezone(tz).naive_local(),
+            IcalZone::Custom(tz_id) => {
+                let naive_utc = date_time.naive_utc();
+                naive_utc + self.custom[tz_id].offset_at(&naive_utc)
+            }
+        }
+    }
+}
+
+impl CustomTimeZone {
+    fn new(timezone: &IcalTimeZone) -> Self {
+        let transitions = timezone
+            .transitions
+            .iter()
+            .filter_map(|transition| {
+                let dtstart = property_value(&transition.properties, "DTSTART")?;
+                let mut rrule_lines = vec![format!("DTSTART:{}", dtstart)];
+                if let Some(rrule) = property_value(&transition.properties, "RRULE") {
+                    rrule_lines.push(format!("RRULE:{}", rrule));
+                }
+
+                Some(Transition {
+                    rrule_lines,
+                    first_onset: parse_ical_naive_date_time(&dtstart)?,
+                    offset_to: parse_utc_offset(&property_value(
+                        &transition.properties,
+                        "TZOFFSETTO",
+                    )?)?,
+                })
+            })
+            .collect();
+
+        Self { transitions }
+    }
+
+    /// Offset of the transition with the latest onset before the date-time.
+    /// The onsets are compared with the date-time directly, which is off by the offset around the transitions only.
+    fn offset_at(&self, date_time: &NaiveDateTime) -> Duration {
+        self.transitions
+            .iter()
+            .filter_map(|transition| {
+                let onset = if transition.rrule_lines.len() > 1 {
+                    let rrule_set: RRuleSet = transition.rrule_lines.join("\n").parse().ok()?;
+                    rrule_set
+                        .before(UTC.from_utc_datetime(date_time), true)?
+                        .naive_utc()
+                } else {
+                    transition.first_onset
+                };
+
+                if onset <= *date_time {
+                    Some((onset, transition.offset_to))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(onset, _)| *onset)
+            .map(|(_, offset)| offset)
+            .or_else(|| {
+                self.transitions
+                    .first()
+                    .map(|transition| transition.offset_to)
+            })
+            .unwrap_or_else(Duration::zero)
+    }
+}
+
+pub fn parse_ical_naive_date_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()
+}
+
+/// Value of a parameter of the property, e.g. "Europe/Paris" for "DTSTART;TZID=Europe/Paris:..."
+pub fn param_value(property: &Property, name: &str) -> Option<String> {
+    property
+        .params
+        .as_ref()?
+        .iter()
+        .find(|(param_name, _)| param_name.eq_ignore_ascii_case(name))
+        .and_then(|(_, values)| values.first())
+        .map(|value| value.trim_matches('"').to_string())
+}
+
+pub fn property_value(properties: &[Property], name: &str) -> Option<String> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .and_then(|property| property.value.clone())
+}
+
+/// Finds a zone of the tz database, from its IANA or Windows name
+pub fn resolve_zone_name(name: &str) -> Option<Tz> {
+    let name = name.trim();
+    if let Ok(tz) = name.parse::<Tz>() {
+        return Some(tz);
+    }
+
+    WINDOWS_ZONES
+        .iter()
+        .find(|(windows_name, _)| windows_name.eq_ignore_ascii_case(name))
+        .and_then(|(_, iana_name)| iana_name.parse::<Tz>().ok())
+        // Some exports prefix the IANA name, e.g. "/mozilla.org/20050126_1/Europe/Paris"
+        .or_else(|| {
+            name.match_indices('/')
+                .map(|(index, _)| &name[index + 1..])
+                .find_map(|suffix| suffix.parse::<Tz>().ok())
+        })
+}
+
+// Wall-clock times in a DST gap don't exist, so they are shifted by an hour
+pub fn local_to_utc(tz: &Tz, date_time: &NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(date_time)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(*date_time + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|date_time| date_time.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(date_time))
+}
+
+// e.g. "+0200", "-0500" or "+053000"
+fn parse_utc_offset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let sign = match value.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = &value[1..];
+    let hours: i64 = digits.get(0..2)?.parse().ok()?;
+    let minutes: i64 = digits.get(2..4)?.parse().ok()?;
+    let seconds: i64 = digits
+        .get(4..6)
+        .map(|s| s.parse().ok())
+        .unwrap_or(Some(0))?;
+
+    Some(Duration::seconds(
+        sign * (hours * 3600 + minutes * 60 + seconds),
+    ))
+}
+
+// Windows time zone names, as used by Outlook and Exchange, and their main IANA zone (from the CLDR mapping)
+const WINDOWS_ZONES: &[(&str, &str)] = &[
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("UTC-11", "Etc/GMT+11"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("Pacific Standard Time (Mexico)", "America/Tijuana"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Central America Standard Time", "America/Guatemala"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Central Standard Time (Mexico)", "America/Mexico_City"),
+    ("Canada Central Standard Time", "America/Regina"),
+    ("SA Pacific Standard Time", "America/Bogota"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("US Eastern Standard Time", "America/Indianapolis"),
+    ("Venezuela Standard Time", "America/Caracas"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("SA Western Standard Time", "America/La_Paz"),
+    ("Pacific SA Standard Time", "America/Santiago"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("Argentina Standard Time", "America/Buenos_Aires"),
+    ("SA Eastern Standard Time", "America/Cayenne"),
+    ("Greenland Standard Time", "America/Godthab"),
+    ("Montevideo Standard Time", "America/Montevideo"),
+    ("UTC-02", "Etc/GMT+2"),
+    ("Azores Standard Time", "Atlantic/Azores"),
+    ("Cape Verde Standard Time", "Atlantic/Cape_Verde"),
+    ("UTC", "Etc/UTC"),
+    ("GMT Standard Time", "Europe/London"),
+    ("Greenwich Standard Time", "Atlantic/Reykjavik"),
+    ("Morocco Standard Time", "Africa/Casablanca"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("W. Central Africa Standard Time", "Africa/Lagos"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("Middle East Standard Time", "Asia/Beirut"),
+    ("Egypt Standard Time", "Africa/Cairo"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+    ("FLE Standard Time", "Europe/Kiev"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("Kaliningrad Standard Time", "Europe/Kaliningrad"),
+    ("Turkey Standard Time", "Europe/Istanbul"),
+    ("Arabic Standard Time", "Asia/Baghdad"),
+    ("Arab Standard Time", "Asia/Riyadh"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("E. Africa Standard Time", "Africa/Nairobi"),
+    ("Iran Standard Time", "Asia/Tehran"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("Azerbaijan Standard Time", "Asia/Baku"),
+    ("Georgian Standard Time", "Asia/Tbilisi"),
+    ("Afghanistan Standard Time", "Asia/Kabul"),
+    ("Ekaterinburg Standard Time", "Asia/Yekaterinburg"),
+    ("Pakistan Standard Time", "Asia/Karachi"),
+    ("India Standard Time", "Asia/Calcutta"),
+    ("Sri Lanka Standard Time", "Asia/Colombo"),
+    ("Nepal Standard Time", "Asia/Katmandu"),
+    ("Central Asia Standard Time", "Asia/Almaty"),
+    ("Bangladesh Standard Time", "Asia/Dhaka"),
+    ("Myanmar Standard Time", "Asia/Rangoon"),
+    ("SE Asia Standard Time", "Asia/Bangkok"),
+    ("North Asia Standard Time", "Asia/Krasnoyarsk"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("W. Australia Standard Time", "Australia/Perth"),
+    ("Taipei Standard Time", "Asia/Taipei"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("Cen. Australia Standard Time", "Australia/Adelaide"),
+    ("AUS Central Standard Time", "Australia/Darwin"),
+    ("E. Australia Standard Time", "Australia/Brisbane"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("West Pacific Standard Time", "Pacific/Port_Moresby"),
+    ("Tasmania Standard Time", "Australia/Hobart"),
+    ("Vladivostok Standard Time", "Asia/Vladivostok"),
+    ("Central Pacific Standard Time", "Pacific/Guadalcanal"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+    ("UTC+12", "Etc/GMT-12"),
+    ("Fiji Standard Time", "Pacific/Fiji"),
+    ("Tonga Standard Time", "Pacific/Tongatapu"),
+];
@@ -0,0 +1,62 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::fmt;
+use std::str::FromStr;
+
+/// Daily working hours, e.g. "09:00-18:00"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkingHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl WorkingHours {
+    pub fn start_on(&self, date: &NaiveDate) -> NaiveDateTime {
+        date.and_time(self.start)
+    }
+
+    pub fn end_on(&self, date: &NaiveDate) -> NaiveDateTime {
+        date.and_time(self.end)
+    }
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        Self {
+            start: NaiveTime::from_hms(9, 0, 0),
+            end: NaiveTime::from_hms(18, 0, 0),
+        }
+    }
+}
+
+impl FromStr for WorkingHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_time = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+
+        match s.split_once('-') {
+            Some((start, end)) => match (parse_time(start), parse_time(end)) {
+                (Some(start), Some(end)) if start < end => Ok(Self { start, end }),
+                _ => Err(format!(
+                    "Invalid working hours '{}'. Expected format: 'HH:MM-HH:MM', with the start before the end",
+                    s
+                )),
+            },
+            None => Err(format!(
+                "Invalid working hours '{}'. Expected format: 'HH:MM-HH:MM'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WorkingHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
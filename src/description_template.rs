@@ -0,0 +1,147 @@
+use crate::calendar::models::meeting::Meeting;
+use crate::jira::models::sprint::Sprint;
+use crate::tempo::planner::PlannedIssue;
+use crate::Issue;
+use regex::{Captures, Regex};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+// e.g. "{summary}"
+static PLACEHOLDER_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([a-z]+)\}").unwrap());
+
+pub const ISSUE_DESCRIPTION_FIELDS: &[&str] = &[
+    "key",
+    "summary",
+    "status",
+    "type",
+    "priority",
+    "epic",
+    "labels",
+    "components",
+    "sprint",
+    "run",
+];
+pub const MEETING_DESCRIPTION_FIELDS: &[&str] = &[
+    "key",
+    "title",
+    "location",
+    "organizer",
+    "attendees",
+    "categories",
+    "start",
+    "end",
+    "run",
+];
+pub const PLANNED_ISSUE_DESCRIPTION_FIELDS: &[&str] = &["key", "description", "run"];
+
+/// Description of a work log, with `{field}` placeholders (e.g. "{summary} [{status}]")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptionTemplate {
+    pub template: String,
+}
+
+impl DescriptionTemplate {
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+        }
+    }
+
+    /// Names of the placeholders of the template
+    pub fn fields(&self) -> Vec<String> {
+        PLACEHOLDER_REGEX
+            .captures_iter(&self.template)
+            .map(|captures| captures[1].to_string())
+            .collect()
+    }
+
+    /// Fields of the template which are not among the known ones
+    pub fn unknown_fields(&self, known_fields: &[&str]) -> Vec<String> {
+        self.fields()
+            .into_iter()
+            .filter(|field| !known_fields.contains(&field.as_str()))
+            .collect()
+    }
+
+    /// Replaces the placeholders by the values of the fields
+    pub fn render(&self, values: &[(&str, String)]) -> String {
+        PLACEHOLDER_REGEX
+            .replace_all(&self.template, |captures: &Captures| {
+                values
+                    .iter()
+                    .find(|(name, _)| *name == &captures[1])
+                    .map(|(_, value)| value.to_string())
+                    .unwrap_or_else(|| captures[0].to_string())
+            })
+            .trim()
+            .to_string()
+    }
+
+    pub fn render_issue(&self, issue: &Issue, sprint: &Sprint, run: &str) -> String {
+        let fields = &issue.fields;
+        self.render(&[
+            ("key", issue.key.to_string()),
+            ("summary", fields.summary.to_string()),
+            ("status", fields.status.name.to_string()),
+            ("type", fields.issue_type.name.to_string()),
+            ("priority", fields.priority.name.to_string()),
+            (
+                "epic",
+                fields
+                    .epic
+                    .as_ref()
+                    .map(|epic| epic.name.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("labels", fields.labels.join(", ")),
+            (
+                "components",
+                fields
+                    .components
+                    .iter()
+                    .map(|component| component.name.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+            ("sprint", sprint.name.to_string()),
+            ("run", run.to_string()),
+        ])
+    }
+
+    pub fn render_meeting(&self, meeting: &Meeting, key: &str, run: &str) -> String {
+        self.render(&[
+            ("key", key.to_string()),
+            ("title", meeting.title.to_string()),
+            ("location", meeting.location.to_string()),
+            ("organizer", meeting.organizer.clone().unwrap_or_default()),
+            ("attendees", meeting.attendees.len().to_string()),
+            ("categories", meeting.categories.join(", ")),
+            ("start", meeting.start_time.format("%H:%M").to_string()),
+            ("end", meeting.end_time.format("%H:%M").to_string()),
+            ("run", run.to_string()),
+        ])
+    }
+
+    pub fn render_planned_issue(&self, planned_issue: &PlannedIssue, run: &str) -> String {
+        self.render(&[
+            ("key", planned_issue.key.to_string()),
+            ("description", planned_issue.description.to_string()),
+            ("run", run.to_string()),
+        ])
+    }
+}
+
+impl FromStr for DescriptionTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(DescriptionTemplate::new(s))
+    }
+}
+
+impl fmt::Display for DescriptionTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.template)
+    }
+}
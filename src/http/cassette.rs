@@ -0,0 +1,298 @@
+use crate::utils::hash::stable_hash;
+use chrono::NaiveDate;
+use log::warn;
+use regex::{Captures, Regex};
+use reqwest::{Request, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+
+const RUN_FILE_NAME: &str = "run.json";
+const CALENDAR_FILE_NAME: &str = "calendar.ics";
+const REDACTED_EMAIL_DOMAIN: &str = "redacted.invalid";
+
+// Also matches the emails encoded in the urls, e.g. "john.doe%40company.com"
+static EMAIL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._+-]+(@|%40)[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// A directory storing every request of a run and its response, to replay the run offline
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    pub mode: CassetteMode,
+    pub dir: String,
+    // Shared by the clones given to all the HTTP clients, so the files are numbered in the order of the requests
+    next_interaction: Arc<AtomicUsize>,
+    // Loaded by the first replayed request, then shared by all the HTTP clients so each response is served once
+    player: Arc<OnceLock<CassettePlayer>>,
+}
+
+/// A recorded request and its response. Headers (and thus credentials) are never stored,
+/// and the emails are replaced by pseudonyms.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    // Path and query only, so a run can be replayed with another host
+    pub url: String,
+    pub request_body: Option<Value>,
+    pub status: u16,
+    pub response_body: Value,
+}
+
+/// What's needed, besides the requests, to replay a run exactly
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordedRun {
+    // Pseudonym of the user email, as found in the recorded responses
+    pub email: String,
+    pub date: NaiveDate,
+    // Seed of the randomness added to the time scores
+    pub seed: u64,
+}
+
+/// Serves the recorded responses, in the order they were recorded
+#[derive(Debug)]
+pub struct CassettePlayer {
+    dir: String,
+    interactions: Mutex<Vec<Interaction>>,
+    // The replayed requests, with the recorded responses
+    replayed: Mutex<Vec<Interaction>>,
+}
+
+impl Cassette {
+    pub fn new(mode: CassetteMode, dir: &str) -> Self {
+        Self {
+            mode,
+            dir: dir.to_string(),
+            next_interaction: Arc::new(AtomicUsize::new(1)),
+            player: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Creates the directory, which must be empty so no interaction of a previous run is replayed
+    pub fn start_recording(&self) {
+        let is_empty = match fs::read_dir(&self.dir) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(_) => true,
+        };
+        if !is_empty {
+            panic!(
+                "The cassette directory {} is not empty, remove it or record to another directory",
+                self.dir
+            );
+        }
+
+        fs::create_dir_all(&self.dir).unwrap_or_else(|e| {
+            panic!(
+                "Could not create the cassette directory {}: {}",
+                self.dir, e
+            )
+        });
+        self.next_interaction.store(1, Ordering::SeqCst);
+    }
+
+    pub fn save_run(&self, run: &RecordedRun) {
+        write_json(&self.file_path(RUN_FILE_NAME), run);
+    }
+
+    pub fn load_run(&self) -> RecordedRun {
+        read_json(&self.file_path(RUN_FILE_NAME))
+    }
+
+    pub fn record(&self, request: &Request, status: StatusCode, body: &str) {
+        let interaction = Interaction {
+            method: request.method().to_string(),
+            url: redacted_url(request),
+            request_body: redacted_request_body(request),
+            status: status.as_u16(),
+            response_body: redacted_json(body),
+        };
+
+        let index = self.next_interaction.fetch_add(1, Ordering::SeqCst);
+        write_json(
+            &self.file_path(&format!(
+                "{:04}-{}.json",
+                index,
+                interaction.method.to_lowercase()
+            )),
+            &interaction,
+        );
+    }
+
+    pub fn replay(&self, request: &Request) -> (StatusCode, String) {
+        self.player
+            .get_or_init(|| CassettePlayer::new(self))
+            .replay(request)
+    }
+
+    /// The requests sent so far by the replayed run (e.g. the work logs it would have posted)
+    pub fn replayed_interactions(&self) -> Vec<Interaction> {
+        match self.player.get() {
+            Some(player) => player.replayed.lock().unwrap().clone(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn save_calendar(&self, index: usize, content: &str) {
+        let path = self.file_path(&calendar_file_name(index));
+        fs::write(&path, redact_emails(content))
+            .unwrap_or_else(|e| panic!("Could not write {}: {}", path, e));
+    }
+
+    pub fn load_calendar(&self, index: usize) -> String {
+        let path = self.file_path(&calendar_file_name(index));
+        fs::read_to_string(&path).unwrap_or_else(|e| panic!("No recorded calendar {}: {}", path, e))
+    }
+
+    fn file_path(&self, file_name: &str) -> String {
+        Path::new(&self.dir)
+            .join(file_name)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+impl CassettePlayer {
+    pub fn new(cassette: &Cassette) -> Self {
+        let mut file_names: Vec<String> = fs::read_dir(&cassette.dir)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Could not read the cassette directory {}: {}",
+                    cassette.dir, e
+                )
+            })
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| {
+                name.starts_with(|c: char| c.is_ascii_digit()) && name.ends_with(".json")
+            })
+            .collect();
+        file_names.sort();
+
+        Self {
+            dir: cassette.dir.to_string(),
+            interactions: Mutex::new(
+                file_names
+                    .iter()
+                    .map(|name| read_json(&cassette.file_path(name)))
+                    .collect(),
+            ),
+            replayed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the status and body of the first unused response recorded for the same method and url
+    pub fn replay(&self, request: &Request) -> (StatusCode, String) {
+        let method = request.method().to_string();
+        let url = redacted_url(request);
+
+        let mut interactions = self.interactions.lock().unwrap();
+        let position = interactions
+            .iter()
+            .position(|interaction| interaction.method == method && interaction.url == url)
+            .unwrap_or_else(|| {
+                panic!(
+                    "No recorded response for {} {} in {}. Was the run recorded with the same options?",
+                    method, url, self.dir
+                )
+            });
+        let interaction = interactions.remove(position);
+
+        let request_body = redacted_request_body(request);
+        if request_body != interaction.request_body {
+            warn!(
+                "The body of {} {} differs from the recorded one:\n{}",
+                method,
+                url,
+                request_body.clone().unwrap_or(Value::Null)
+            );
+        }
+
+        let status = StatusCode::from_u16(interaction.status).unwrap();
+        let body = match &interaction.response_body {
+            Value::String(body) => body.to_string(),
+            json => json.to_string(),
+        };
+        self.replayed.lock().unwrap().push(Interaction {
+            request_body,
+            ..interaction
+        });
+
+        (status, body)
+    }
+}
+
+/// Replaces an email by a stable pseudonym, so the same user can still be matched in the recorded responses
+pub fn redact_email(email: &str) -> String {
+    if email.ends_with(REDACTED_EMAIL_DOMAIN) {
+        return email.to_string();
+    }
+
+    format!(
+        "redacted-{:08x}@{}",
+        stable_hash(&email.to_lowercase()) as u32,
+        REDACTED_EMAIL_DOMAIN
+    )
+}
+
+fn redact_emails(text: &str) -> String {
+    EMAIL_REGEX
+        .replace_all(text, |captures: &Captures| {
+            let redacted = redact_email(&captures[0].replace("%40", "@"));
+            if &captures[1] == "@" {
+                redacted
+            } else {
+                redacted.replace('@', "%40")
+            }
+        })
+        .to_string()
+}
+
+// The first calendar keeps the name it had when a single calendar was supported
+fn calendar_file_name(index: usize) -> String {
+    match index {
+        0 => CALENDAR_FILE_NAME.to_string(),
+        _ => format!("calendar-{}.ics", index + 1),
+    }
+}
+
+fn redacted_url(request: &Request) -> String {
+    let url = request.url();
+    let path_and_query = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    redact_emails(&path_and_query)
+}
+
+fn redacted_request_body(request: &Request) -> Option<Value> {
+    request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(|bytes| redacted_json(&String::from_utf8_lossy(bytes)))
+}
+
+// Bodies are stored as JSON when possible, to keep the cassettes readable
+fn redacted_json(body: &str) -> Value {
+    let body = redact_emails(body);
+    serde_json::from_str(&body).unwrap_or(Value::String(body))
+}
+
+fn write_json<T: Serialize>(path: &str, value: &T) {
+    fs::write(path, serde_json::to_string_pretty(value).unwrap())
+        .unwrap_or_else(|e| panic!("Could not write {}: {}", path, e));
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &str) -> T {
+    let content =
+        fs::read_to_string(path).unwrap_or_else(|e| panic!("Could not read {}: {}", path, e));
+    serde_json::from_str(&content)
+        .unwrap_or_else(|e| panic!("Invalid cassette file {}: {}", path, e))
+}
@@ -1,8 +1,13 @@
+use crate::http::cassette::CassetteMode;
 use crate::http::http_client::Credentials::{Bearer, UsernamePassword};
-use reqwest::{Client, RequestBuilder};
+use crate::http::pagination::{NextPage, Paginated};
+use crate::NetworkConfig;
+use reqwest::{Certificate, Client, Proxy, Request, RequestBuilder, StatusCode};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fs;
+use url::Url;
 
 #[derive(Debug)]
 pub enum Credentials {
@@ -12,9 +17,9 @@ pub enum Credentials {
 
 #[derive(Debug)]
 pub struct HttpClientConfig {
-    pub host: String,
     pub base_url: String,
     pub credentials: Credentials,
+    pub network: NetworkConfig,
 }
 
 #[derive(Debug)]
@@ -24,19 +29,53 @@ pub struct HttpClient {
 }
 
 impl HttpClientConfig {
-    pub fn new(host: &str, base_path: &str, credentials: Credentials) -> Self {
+    /// The url can either be a host name (HTTPS is then used), or a full url with a scheme and an optional path
+    /// (e.g. "http://localhost:8080" or "https://gateway.company.com/jira")
+    pub fn new(
+        url: &str,
+        base_path: &str,
+        credentials: Credentials,
+        network: &NetworkConfig,
+    ) -> Self {
+        let url = url.trim_end_matches('/');
+        let base_url = if url.contains("://") {
+            format!("{}/{}", url, base_path)
+        } else {
+            format!("https://{}/{}", url, base_path)
+        };
+
         Self {
-            host: host.to_string(),
             credentials,
-            base_url: format!("https://{}/{}", host, base_path),
+            base_url,
+            network: network.clone(),
         }
     }
 }
 
+/// A client going through the proxy and trusting the CA bundle of the configuration, if any
+pub fn network_client(network: &NetworkConfig) -> Client {
+    let mut builder = Client::builder();
+
+    if let Some(proxy) = &network.proxy {
+        builder = builder.proxy(
+            Proxy::all(proxy.as_str())
+                .unwrap_or_else(|e| panic!("Invalid proxy url {}: {}", proxy, e)),
+        );
+    }
+
+    if let Some(ca_bundle) = &network.ca_bundle {
+        for certificate in read_ca_bundle(ca_bundle) {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder.build().unwrap()
+}
+
 impl HttpClient {
     pub fn new(config: HttpClientConfig) -> Self {
         Self {
-            client: Client::new(),
+            client: network_client(&config.network),
             config,
         }
     }
@@ -45,7 +84,56 @@ impl HttpClient {
         let request_builder = self
             .client
             .get(format!("{}/{}", self.config.base_url, path));
-        return self.send_request(request_builder).await;
+        self.send_request(request_builder).await
+    }
+
+    /// Same as `get`, but with an absolute url (e.g. the "self" link of a resource)
+    pub async fn get_url<T: DeserializeOwned>(&self, url: &str) -> T {
+        self.send_request(self.client.get(url)).await
+    }
+
+    /// Fetches every page of a paginated endpoint, and returns all the items
+    pub async fn get_all<P: Paginated>(&self, path: &str) -> Vec<P::Item> {
+        self.fetch_all::<P>(path, None).await
+    }
+
+    /// Same as `get_all`, for a search endpoint taking its criteria in the body (sent again for every page)
+    pub async fn post_all<P: Paginated>(
+        &self,
+        path: &str,
+        payload: &HashMap<String, Value>,
+    ) -> Vec<P::Item> {
+        self.fetch_all::<P>(path, Some(payload)).await
+    }
+
+    async fn fetch_all<P: Paginated>(
+        &self,
+        path: &str,
+        payload: Option<&HashMap<String, Value>>,
+    ) -> Vec<P::Item> {
+        let mut url = format!("{}/{}", self.config.base_url, path);
+        let mut items = Vec::new();
+
+        loop {
+            let request_builder = match payload {
+                Some(p) => self.client.post(&url).json(p),
+                None => self.client.get(&url),
+            };
+            let page = self.send_request::<P>(request_builder).await;
+            let next_page = page.next_page();
+            items.extend(page.into_items());
+
+            match next_page {
+                Some(NextPage::StartAt(start_at)) => {
+                    url = with_query(&url, &[("startAt".to_string(), start_at.to_string())])
+                }
+                // The next url is rebuilt from the configured one, in case Tempo is behind a gateway
+                Some(NextPage::Url(next_url)) => url = with_query(&url, &page_query(&next_url)),
+                None => break,
+            }
+        }
+
+        items
     }
 
     pub async fn post<T: DeserializeOwned>(
@@ -61,7 +149,7 @@ impl HttpClient {
             request_builder = request_builder.json(p);
         }
 
-        return self.send_request(request_builder).await;
+        self.send_request(request_builder).await
     }
 
     async fn send_request<T: DeserializeOwned>(&self, request_builder: RequestBuilder) -> T {
@@ -73,10 +161,7 @@ impl HttpClient {
             }
         }
 
-        let response = builder.send().await.unwrap();
-
-        let status = response.status();
-        let body = response.text().await.unwrap();
+        let (status, body) = self.execute(builder.build().unwrap()).await;
 
         if status.is_server_error() {
             panic!("A server error occurred ({}): {}", status, body);
@@ -85,12 +170,130 @@ impl HttpClient {
         }
 
         let json = serde_json::from_str(body.as_str());
-        return match json {
+        match json {
             Ok(data) => data,
             Err(e) => panic!(
                 "Could not parse response JSON. Reason: {}. Response body: \n{}",
                 e, body
             ),
+        }
+    }
+
+    /// Sends the request, or replays it from the cassette. The exchange is recorded if needed.
+    async fn execute(&self, request: Request) -> (StatusCode, String) {
+        let recorded_request = match &self.config.network.cassette {
+            Some(cassette) if cassette.mode == CassetteMode::Replay => {
+                return cassette.replay(&request)
+            }
+            Some(cassette) if cassette.mode == CassetteMode::Record => request.try_clone(),
+            _ => None,
         };
+
+        let response = self.client.execute(request).await.unwrap();
+        let status = response.status();
+        let body = response.text().await.unwrap();
+
+        if let (Some(cassette), Some(request)) = (&self.config.network.cassette, recorded_request) {
+            cassette.record(&request, status, &body);
+        }
+
+        (status, body)
+    }
+}
+
+/// Replaces (or adds) the parameters in the query of the url
+fn with_query(url: &str, params: &[(String, String)]) -> String {
+    let mut parsed_url = Url::parse(url).unwrap();
+    let query_pairs: Vec<(String, String)> = parsed_url
+        .query_pairs()
+        .filter(|(key, _)| !params.iter().any(|(param, _)| param == key))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+    parsed_url
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(query_pairs)
+        .extend_pairs(params);
+
+    parsed_url.to_string()
+}
+
+/// The "offset" and "limit" parameters of a Tempo next page url
+fn page_query(next_url: &str) -> Vec<(String, String)> {
+    Url::parse(next_url)
+        .unwrap_or_else(|e| panic!("Invalid next page url {}: {}", next_url, e))
+        .query_pairs()
+        .filter(|(key, _)| key == "offset" || key == "limit")
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Reads all the certificates of a PEM bundle
+fn read_ca_bundle(path: &str) -> Vec<Certificate> {
+    let bundle = fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Could not read the CA bundle from {}", path));
+    let end_marker = "-----END CERTIFICATE-----";
+
+    bundle
+        .split_inclusive(end_marker)
+        .filter(|pem| pem.contains(end_marker))
+        .map(|pem| {
+            Certificate::from_pem(pem.trim().as_bytes())
+                .unwrap_or_else(|e| panic!("Invalid certificate in {}: {}", path, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_url(url: &str) -> String {
+        HttpClientConfig::new(
+            url,
+            "rest/api/3",
+            Bearer("token".to_string()),
+            &NetworkConfig::default(),
+        )
+        .base_url
+    }
+
+    #[test]
+    fn builds_the_base_url_from_a_host_or_an_url() {
+        assert_eq!(
+            base_url("company.atlassian.net"),
+            "https://company.atlassian.net/rest/api/3"
+        );
+        assert_eq!(
+            base_url("company.atlassian.net/"),
+            "https://company.atlassian.net/rest/api/3"
+        );
+        assert_eq!(
+            base_url("http://localhost:8080"),
+            "http://localhost:8080/rest/api/3"
+        );
+        assert_eq!(
+            base_url("https://gateway.company.com/jira/"),
+            "https://gateway.company.com/jira/rest/api/3"
+        );
+    }
+
+    #[test]
+    fn reads_every_certificate_of_the_ca_bundle() {
+        let path = format!(
+            "{}/tests/fixtures/ca_bundle.pem",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        assert_eq!(read_ca_bundle(&path).len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid proxy url")]
+    fn rejects_an_invalid_proxy_url() {
+        network_client(&NetworkConfig {
+            proxy: Some("ftp://proxy.company.com".to_string()),
+            ..NetworkConfig::default()
+        });
     }
 }
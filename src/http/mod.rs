@@ -1 +1,3 @@
+pub mod cassette;
 pub mod http_client;
+pub mod pagination;
@@ -0,0 +1,17 @@
+use serde::de::DeserializeOwned;
+
+pub enum NextPage {
+    // Atlassian style: index of the first item of the next page (sent as "startAt")
+    StartAt(i32),
+    // Tempo style: full url of the next page (from "metadata.next"), only its "offset" and "limit" are used
+    Url(String),
+}
+
+/// A response that only contains a page of the results
+pub trait Paginated: DeserializeOwned {
+    type Item;
+
+    fn next_page(&self) -> Option<NextPage>;
+
+    fn into_items(self) -> Vec<Self::Item>;
+}
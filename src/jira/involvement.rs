@@ -0,0 +1,204 @@
+use crate::jira::models::issue::Issue;
+use chrono::{Duration, NaiveDate};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvolvementRole {
+    Assignee,
+    Reporter,
+    Watcher,
+    Commenter,
+    PreviousAssignee,
+}
+
+/// How the current user is involved in an issue. A role without weight is excluded,
+/// i.e. being involved only through that role does not keep the issue.
+#[derive(Debug, Clone, Copy)]
+pub struct InvolvementRule {
+    pub role: InvolvementRole,
+    pub weight: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Involvement {
+    pub rules: Vec<InvolvementRule>,
+    // A comment is "recent" if it was posted at most this number of days before the logged date
+    pub recent_comment_days: i64,
+}
+
+impl InvolvementRole {
+    fn name(&self) -> &'static str {
+        match self {
+            InvolvementRole::Assignee => "assignee",
+            InvolvementRole::Reporter => "reporter",
+            InvolvementRole::Watcher => "watcher",
+            InvolvementRole::Commenter => "commenter",
+            InvolvementRole::PreviousAssignee => "previous-assignee",
+        }
+    }
+}
+
+impl FromStr for InvolvementRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "assignee" => Ok(InvolvementRole::Assignee),
+            "reporter" => Ok(InvolvementRole::Reporter),
+            "watcher" => Ok(InvolvementRole::Watcher),
+            "commenter" => Ok(InvolvementRole::Commenter),
+            "previous-assignee" => Ok(InvolvementRole::PreviousAssignee),
+            _ => Err(format!(
+                "Unknown involvement role '{}'. Expected one of: assignee, reporter, watcher, commenter, previous-assignee",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for InvolvementRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (role, weight) = match s.split_once('=') {
+            Some((role, weight)) => (role, weight.trim()),
+            None => (s, "1"),
+        };
+
+        // A null or negative weight would cancel the time score of the issue, instead of excluding it
+        let weight = match weight {
+            "off" => None,
+            _ => Some(
+                weight
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|weight| weight.is_finite() && *weight > 0.0)
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid involvement weight '{}'. Expected a positive number, or 'off' to exclude the role",
+                            weight
+                        )
+                    })?,
+            ),
+        };
+
+        Ok(Self {
+            role: role.parse()?,
+            weight,
+        })
+    }
+}
+
+impl fmt::Display for InvolvementRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.weight {
+            Some(weight) => write!(f, "{}={}", self.role.name(), weight),
+            None => write!(f, "{}=off", self.role.name()),
+        }
+    }
+}
+
+impl Default for Involvement {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                InvolvementRule {
+                    role: InvolvementRole::Assignee,
+                    weight: Some(2.0),
+                },
+                InvolvementRule {
+                    role: InvolvementRole::Reporter,
+                    weight: Some(1.0),
+                },
+            ],
+            recent_comment_days: 7,
+        }
+    }
+}
+
+impl Involvement {
+    /// Overrides the rules of the same role, or adds the rule if the role was not configured yet
+    pub fn with_rules(mut self, rules: &[InvolvementRule]) -> Self {
+        for rule in rules.iter() {
+            self.rules.retain(|r| r.role != rule.role);
+            self.rules.push(*rule);
+        }
+        self
+    }
+
+    pub fn is_enabled(&self, role: InvolvementRole) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.role == role && rule.weight.is_some())
+    }
+
+    /// Returns the highest weight among the roles the user has on the issue,
+    /// or None if the user is not involved through any enabled role
+    pub fn weight(
+        &self,
+        issue: &Issue,
+        email: &str,
+        account_id: Option<&str>,
+        date: &NaiveDate,
+    ) -> Option<f64> {
+        let comments_since = *date - Duration::days(self.recent_comment_days);
+
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let weight = rule.weight?;
+                let has_role = match rule.role {
+                    InvolvementRole::Assignee => issue.is_assigned_to(email),
+                    InvolvementRole::Reporter => issue.was_reported_by(email),
+                    InvolvementRole::Watcher => issue.is_watched_by_current_user(),
+                    InvolvementRole::Commenter => account_id
+                        .map(|id| issue.was_commented_by_since(id, &comments_since))
+                        .unwrap_or(false),
+                    InvolvementRole::PreviousAssignee => account_id
+                        .map(|id| issue.was_previously_assigned_to(id))
+                        .unwrap_or(false),
+                };
+
+                if has_role {
+                    Some(weight)
+                } else {
+                    None
+                }
+            })
+            .fold(None, |max: Option<f64>, weight| {
+                Some(max.map_or(weight, |m| m.max(weight)))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_involvement_rules() {
+        let rule: InvolvementRule = "watcher=1.5".parse().unwrap();
+        assert_eq!(rule.role, InvolvementRole::Watcher);
+        assert_eq!(rule.weight, Some(1.5));
+
+        let rule: InvolvementRule = "reporter=off".parse().unwrap();
+        assert_eq!(rule.weight, None);
+
+        let rule: InvolvementRule = "commenter".parse().unwrap();
+        assert_eq!(rule.weight, Some(1.0));
+    }
+
+    #[test]
+    fn rejects_the_weights_that_are_not_positive() {
+        for rule in [
+            "watcher=0",
+            "commenter=-2",
+            "assignee=NaN",
+            "reporter=inf",
+            "watcher=x",
+        ] {
+            assert!(rule.parse::<InvolvementRule>().is_err(), "{}", rule);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::jira::models::issue::Issue;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueFilterField {
+    Type,
+    Label,
+    Component,
+    Status,
+    StatusCategory,
+    Epic,
+}
+
+/// A single `<field>=<value>` rule, matched case-insensitively against an issue
+#[derive(Debug, Clone)]
+pub struct IssueFilter {
+    pub field: IssueFilterField,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IssueFilters {
+    pub include: Vec<IssueFilter>,
+    pub exclude: Vec<IssueFilter>,
+}
+
+impl IssueFilterField {
+    fn name(&self) -> &'static str {
+        match self {
+            IssueFilterField::Type => "type",
+            IssueFilterField::Label => "label",
+            IssueFilterField::Component => "component",
+            IssueFilterField::Status => "status",
+            IssueFilterField::StatusCategory => "status-category",
+            IssueFilterField::Epic => "epic",
+        }
+    }
+}
+
+impl FromStr for IssueFilterField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "type" => Ok(IssueFilterField::Type),
+            "label" => Ok(IssueFilterField::Label),
+            "component" => Ok(IssueFilterField::Component),
+            "status" => Ok(IssueFilterField::Status),
+            "status-category" => Ok(IssueFilterField::StatusCategory),
+            "epic" => Ok(IssueFilterField::Epic),
+            _ => Err(format!(
+                "Unknown issue filter field '{}'. Expected one of: type, label, component, status, status-category, epic",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for IssueFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((field, value)) if !value.trim().is_empty() => Ok(Self {
+                field: field.parse()?,
+                value: value.trim().to_string(),
+            }),
+            _ => Err(format!(
+                "Invalid issue filter '{}'. Expected format: '<field>=<value>'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for IssueFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.field.name(), self.value)
+    }
+}
+
+impl IssueFilter {
+    pub fn matches(&self, issue: &Issue) -> bool {
+        let fields = &issue.fields;
+        let value = self.value.as_str();
+
+        match self.field {
+            IssueFilterField::Type => fields.issue_type.name.eq_ignore_ascii_case(value),
+            IssueFilterField::Label => fields
+                .labels
+                .iter()
+                .any(|label| label.eq_ignore_ascii_case(value)),
+            IssueFilterField::Component => fields
+                .components
+                .iter()
+                .any(|component| component.name.eq_ignore_ascii_case(value)),
+            IssueFilterField::Status => fields.status.name.eq_ignore_ascii_case(value),
+            // Accept both the display name ("In Progress") and the key ("indeterminate")
+            IssueFilterField::StatusCategory => {
+                let category = &fields.status.status_category;
+                category.name.eq_ignore_ascii_case(value)
+                    || category.key.eq_ignore_ascii_case(value)
+            }
+            IssueFilterField::Epic => fields
+                .epic
+                .as_ref()
+                .map(|epic| epic.key.eq_ignore_ascii_case(value))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl IssueFilters {
+    /// Returns the reason why the issue should be dropped, if any.
+    /// Include filters on the same field are OR-ed, and filters on different fields are AND-ed.
+    pub fn drop_reason(&self, issue: &Issue) -> Option<String> {
+        if let Some(filter) = self.exclude.iter().find(|filter| filter.matches(issue)) {
+            return Some(format!("excluded by {}", filter));
+        }
+
+        let mut checked_fields: Vec<IssueFilterField> = Vec::new();
+        for filter in self.include.iter() {
+            if checked_fields.contains(&filter.field) {
+                continue;
+            }
+            checked_fields.push(filter.field);
+
+            let same_field_filters: Vec<&IssueFilter> = self
+                .include
+                .iter()
+                .filter(|f| f.field == filter.field)
+                .collect();
+
+            if !same_field_filters.iter().any(|f| f.matches(issue)) {
+                return Some(format!(
+                    "not matching {}",
+                    same_field_filters
+                        .iter()
+                        .map(|f| f.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" or ")
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// Splits the issues between the kept ones and the dropped ones (with the reason)
+    pub fn apply(&self, issues: Vec<Issue>) -> (Vec<Issue>, Vec<(Issue, String)>) {
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+
+        for issue in issues.into_iter() {
+            match self.drop_reason(&issue) {
+                Some(reason) => dropped.push((issue, reason)),
+                None => kept.push(issue),
+            }
+        }
+
+        (kept, dropped)
+    }
+}
@@ -1,16 +1,33 @@
 use crate::http::http_client::{Credentials, HttpClient, HttpClientConfig};
+use crate::jira::involvement::{Involvement, InvolvementRole};
 use crate::jira::models::board_configuration::BoardConfiguration;
 use crate::jira::models::issue::Issue;
 use crate::jira::models::list_issues_response::ListIssuesResponse;
 
 use crate::jira::models::list_sprints_response::ListSprintsResponse;
+use crate::jira::models::list_worklogs_response::ListWorkLogsResponse;
+use crate::jira::models::project::Project;
+use crate::jira::models::search_issues_response::{IssueReference, SearchIssuesResponse};
 use crate::jira::models::sprint::Sprint;
+use crate::jira::models::user::User;
+use crate::jira::models::worklog::WorkLog;
+use crate::utils::date::{date_time_to_jira_format, date_to_tempo_format, parse_jira_date};
+use crate::work_log_sink::WorkLogSink;
 use crate::JiraHttpConfig;
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use chrono_tz::{Tz, UTC};
+use serde_json::{json, Number, Value};
+use std::collections::HashMap;
+use url::form_urlencoded::byte_serialize;
 
 #[derive(Debug)]
 pub struct JiraClient {
     client: HttpClient,
+    api_client: HttpClient,
     config: JiraHttpConfig,
+    // Time zone of the work log times
+    timezone: Tz,
 }
 
 impl JiraClient {
@@ -20,11 +37,40 @@ impl JiraClient {
                 &config.host,
                 "rest/agile/1.0",
                 Credentials::UsernamePassword(config.email.to_string(), config.api_key.to_string()),
+                &config.network,
+            )),
+            api_client: HttpClient::new(HttpClientConfig::new(
+                &config.host,
+                "rest/api/3",
+                Credentials::UsernamePassword(config.email.to_string(), config.api_key.to_string()),
+                &config.network,
             )),
             config: config.clone(),
+            timezone: UTC,
         }
     }
 
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub async fn get_myself(&self) -> User {
+        self.api_client.get::<User>("myself").await
+    }
+
+    pub async fn get_issue_reference(&self, issue_key: &str) -> IssueReference {
+        self.api_client
+            .get::<IssueReference>(format!("issue/{}?fields=id", issue_key).as_str())
+            .await
+    }
+
+    pub async fn get_project(&self, project_key: &str) -> Project {
+        self.api_client
+            .get::<Project>(format!("project/{}", project_key).as_str())
+            .await
+    }
+
     pub async fn get_board_configuration(&self, board_id: i32) -> BoardConfiguration {
         self.client
             .get::<BoardConfiguration>(format!("board/{}/configuration", board_id).as_str())
@@ -33,9 +79,10 @@ impl JiraClient {
 
     pub async fn list_active_sprints(&self, board_id: i32) -> Vec<Sprint> {
         self.client
-            .get::<ListSprintsResponse>(format!("board/{}/sprint?state=active", board_id).as_str())
+            .get_all::<ListSprintsResponse>(
+                format!("board/{}/sprint?state=active", board_id).as_str(),
+            )
             .await
-            .values
     }
 
     pub async fn first_active_sprint_for_prefix(
@@ -53,7 +100,8 @@ impl JiraClient {
         &self,
         sprint_id: i32,
         estimation_field: Option<&String>,
-        for_current_user_only: bool,
+        involvement: Option<&Involvement>,
+        date: &NaiveDate,
     ) -> Vec<Issue> {
         let mut fields = vec![
             "issuetype",
@@ -80,25 +128,45 @@ impl JiraClient {
             fields.push(f.as_str());
         }
 
+        // Only request the (heavy) involvement data that is actually needed
+        let mut expand = "";
+        let mut account_id = None;
+        if let Some(inv) = involvement {
+            if inv.is_enabled(InvolvementRole::Watcher) {
+                fields.push("watches");
+            }
+            if inv.is_enabled(InvolvementRole::Commenter) {
+                fields.push("comment");
+            }
+            if inv.is_enabled(InvolvementRole::PreviousAssignee) {
+                expand = "&expand=changelog";
+            }
+            if inv.is_enabled(InvolvementRole::Commenter)
+                || inv.is_enabled(InvolvementRole::PreviousAssignee)
+            {
+                account_id = Some(self.get_myself().await.account_id);
+            }
+        }
+
         let mut issues = self
             .client
-            .get::<ListIssuesResponse>(
+            .get_all::<ListIssuesResponse>(
                 format!(
-                    "sprint/{}/issue?maxResults=1000&fields={}",
+                    "sprint/{}/issue?maxResults=100&fields={}{}",
                     sprint_id,
-                    fields.join(",")
+                    fields.join(","),
+                    expand
                 )
                 .as_str(),
             )
-            .await
-            .issues;
+            .await;
 
-        if for_current_user_only {
+        if let Some(inv) = involvement {
             let email = &self.config.email;
-            issues = issues
-                .into_iter()
-                .filter(|issue| issue.is_assigned_to(email) || issue.was_reported_by(email))
-                .collect();
+            for issue in issues.iter_mut() {
+                issue.involvement_weight = inv.weight(issue, email, account_id.as_deref(), date);
+            }
+            issues.retain(|issue| issue.involvement_weight.is_some());
         }
 
         if let Some(f) = &estimation_field {
@@ -110,3 +178,113 @@ impl JiraClient {
         issues
     }
 }
+
+impl JiraClient {
+    /// Lists the work logs of the current user on the given date, using Jira's native work logs
+    pub async fn list_worklogs(&self, date: &NaiveDate) -> Vec<WorkLog> {
+        let account_id = self.get_myself().await.account_id;
+
+        let jql = format!(
+            "worklogAuthor = currentUser() AND worklogDate = \"{}\"",
+            date_to_tempo_format(date)
+        );
+        let issues = self
+            .api_client
+            .get_all::<SearchIssuesResponse>(
+                format!(
+                    "search?fields=summary&jql={}",
+                    byte_serialize(jql.as_bytes()).collect::<String>()
+                )
+                .as_str(),
+            )
+            .await;
+
+        // The "started" timestamps are only used to narrow the search, the date is checked below
+        let started_after = (*date - Duration::days(1))
+            .and_hms(0, 0, 0)
+            .timestamp_millis();
+        let started_before = (*date + Duration::days(2))
+            .and_hms(0, 0, 0)
+            .timestamp_millis();
+
+        let mut worklogs = Vec::new();
+        for issue in issues.iter() {
+            let issue_worklogs = self
+                .api_client
+                .get_all::<ListWorkLogsResponse>(
+                    format!(
+                        "issue/{}/worklog?startedAfter={}&startedBefore={}",
+                        issue.key, started_after, started_before
+                    )
+                    .as_str(),
+                )
+                .await;
+
+            worklogs.extend(issue_worklogs.into_iter().filter(|worklog| {
+                worklog.author.account_id == account_id
+                    && parse_jira_date(&worklog.started) == Some(*date)
+            }));
+        }
+
+        worklogs
+    }
+}
+
+#[async_trait]
+impl WorkLogSink for JiraClient {
+    async fn logged_time(&self, date: &NaiveDate) -> i32 {
+        self.list_worklogs(date)
+            .await
+            .iter()
+            .map(|worklog| worklog.time_spent_seconds)
+            .sum()
+    }
+
+    async fn post_worklog(
+        &self,
+        date: &NaiveDate,
+        time: Option<&NaiveTime>,
+        issue_key: &str,
+        duration: &i32,
+        description: &str,
+        _billable: bool,
+    ) {
+        // Jira work logs have no billable time (--non-billable-meetings is rejected with this sink)
+        // Without a specific time, log at noon so that the work log stays on the same date
+        // whatever the timezone of the Jira user
+        let started = date_time_to_jira_format(
+            date,
+            time.unwrap_or(&NaiveTime::from_hms(12, 0, 0)),
+            &self.timezone,
+        );
+
+        let mut payload = HashMap::<String, Value>::new();
+        payload.insert("started".to_string(), Value::String(started));
+        payload.insert(
+            "timeSpentSeconds".to_string(),
+            Value::Number(Number::from(*duration)),
+        );
+
+        // Comments must use the Atlassian Document Format, which does not allow empty texts
+        if !description.is_empty() {
+            payload.insert(
+                "comment".to_string(),
+                json!({
+                    "type": "doc",
+                    "version": 1,
+                    "content": [{
+                        "type": "paragraph",
+                        "content": [{ "type": "text", "text": description }]
+                    }]
+                }),
+            );
+        }
+
+        self.api_client
+            .post::<WorkLog>(
+                format!("issue/{}/worklog", issue_key).as_str(),
+                Some(&payload),
+            )
+            .await;
+    }
+}
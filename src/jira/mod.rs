@@ -1,3 +1,5 @@
+pub mod involvement;
+pub mod issue_filter;
 pub mod jira_client;
 pub mod models;
 pub mod time_estimation;
@@ -1,5 +1,6 @@
 use serde::Deserialize;
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct BoardConfiguration {
@@ -14,6 +15,7 @@ pub struct BoardConfiguration {
     pub column_config: ColumnConfig,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct BoardLocation {
@@ -38,9 +40,11 @@ pub struct BoardEstimation {
 #[serde(rename_all = "camelCase")]
 pub struct BoardEstimationField {
     pub field_id: String,
+    #[allow(dead_code)]
     pub display_name: String,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnConfig {
@@ -48,6 +52,7 @@ pub struct ColumnConfig {
     pub columns: Vec<Column>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Column {
@@ -55,6 +60,7 @@ pub struct Column {
     pub statuses: Vec<ColumnStatus>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnStatus {
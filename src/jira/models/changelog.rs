@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Changelog {
+    pub start_at: i32,
+    pub max_results: i32,
+    pub total: i32,
+    pub histories: Vec<ChangelogHistory>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogHistory {
+    pub id: String,
+    pub created: String,
+    pub items: Vec<ChangelogItem>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangelogItem {
+    pub field: String,
+    #[serde(rename = "fieldtype")]
+    pub field_type: String,
+    pub from: Option<String>,
+    #[serde(rename = "fromString")]
+    pub from_label: Option<String>,
+    pub to: Option<String>,
+    #[serde(rename = "toString")]
+    pub to_label: Option<String>,
+}
@@ -0,0 +1,29 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentPage {
+    pub start_at: i32,
+    pub max_results: i32,
+    pub total: i32,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub id: String,
+    pub author: Option<CommentAuthor>,
+    pub created: String,
+    pub updated: String,
+}
+
+// Email addresses are often hidden on comments, so only the account id is relied upon
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentAuthor {
+    pub account_id: String,
+    pub display_name: String,
+}
@@ -1,3 +1,5 @@
+use crate::jira::models::changelog::Changelog;
+use crate::jira::models::comment::CommentPage;
 use crate::jira::models::component::Component;
 use crate::jira::models::epic::Epic;
 use crate::jira::models::priority::Priority;
@@ -5,6 +7,8 @@ use crate::jira::models::resolution::Resolution;
 use crate::jira::models::status::Status;
 use crate::jira::models::time_tracking::TimeTracking;
 use crate::jira::models::user::User;
+use crate::jira::models::watches::Watches;
+use crate::utils::date::parse_jira_date;
 use chrono::NaiveDate;
 use serde::Deserialize;
 use serde_json::Value;
@@ -19,9 +23,13 @@ pub struct Issue {
     pub self_: String,
     pub key: String,
     pub fields: IssueFields,
+    // Only present when requested with "expand=changelog"
+    pub changelog: Option<Changelog>,
 
     // Not parsed from the JSON, but added later
     pub estimation_field_name: Option<String>,
+    pub involvement_weight: Option<f64>,
+    pub planned_seconds: Option<i32>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -55,6 +63,10 @@ pub struct IssueFields {
     #[serde(rename = "workratio")]
     pub work_ratio: i32,
 
+    // Only present when explicitly requested
+    pub watches: Option<Watches>,
+    pub comment: Option<CommentPage>,
+
     #[serde(flatten)]
     pub additional_fields: HashMap<String, Value>,
 }
@@ -82,6 +94,43 @@ impl Issue {
         self.fields.reporter.is(email)
     }
 
+    pub fn is_watched_by_current_user(&self) -> bool {
+        self.fields
+            .watches
+            .as_ref()
+            .map(|watches| watches.is_watching)
+            .unwrap_or(false)
+    }
+
+    pub fn was_commented_by_since(&self, account_id: &str, since: &NaiveDate) -> bool {
+        if let Some(comment_page) = &self.fields.comment {
+            return comment_page.comments.iter().any(|comment| {
+                comment
+                    .author
+                    .as_ref()
+                    .map(|author| author.account_id == account_id)
+                    .unwrap_or(false)
+                    && parse_jira_date(&comment.created)
+                        .map(|created| created >= *since)
+                        .unwrap_or(false)
+            });
+        }
+
+        false
+    }
+
+    pub fn was_previously_assigned_to(&self, account_id: &str) -> bool {
+        if let Some(changelog) = &self.changelog {
+            return changelog.histories.iter().any(|history| {
+                history.items.iter().any(|item| {
+                    item.field == "assignee" && item.from.as_deref() == Some(account_id)
+                })
+            });
+        }
+
+        false
+    }
+
     pub fn is_resolved(&self) -> bool {
         self.fields.resolution.is_some()
     }
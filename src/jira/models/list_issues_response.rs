@@ -1,6 +1,8 @@
+use crate::http::pagination::{NextPage, Paginated};
 use crate::jira::models::issue::Issue;
 use serde::Deserialize;
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ListIssuesResponse {
@@ -10,3 +12,20 @@ pub struct ListIssuesResponse {
     pub total: i32,
     pub issues: Vec<Issue>,
 }
+
+impl Paginated for ListIssuesResponse {
+    type Item = Issue;
+
+    fn next_page(&self) -> Option<NextPage> {
+        let next_start_at = self.start_at + self.issues.len() as i32;
+        if self.issues.is_empty() || next_start_at >= self.total {
+            return None;
+        }
+
+        Some(NextPage::StartAt(next_start_at))
+    }
+
+    fn into_items(self) -> Vec<Issue> {
+        self.issues
+    }
+}
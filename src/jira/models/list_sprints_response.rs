@@ -1,11 +1,29 @@
+use crate::http::pagination::{NextPage, Paginated};
 use crate::jira::models::sprint::Sprint;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ListSprintsResponse {
+    #[allow(dead_code)]
     pub max_results: i32,
     pub start_at: i32,
     pub is_last: bool,
     pub values: Vec<Sprint>,
 }
+
+impl Paginated for ListSprintsResponse {
+    type Item = Sprint;
+
+    fn next_page(&self) -> Option<NextPage> {
+        if self.is_last || self.values.is_empty() {
+            return None;
+        }
+
+        Some(NextPage::StartAt(self.start_at + self.values.len() as i32))
+    }
+
+    fn into_items(self) -> Vec<Sprint> {
+        self.values
+    }
+}
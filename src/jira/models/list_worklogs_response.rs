@@ -0,0 +1,30 @@
+use crate::http::pagination::{NextPage, Paginated};
+use crate::jira::models::worklog::WorkLog;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWorkLogsResponse {
+    pub start_at: i32,
+    #[allow(dead_code)]
+    pub max_results: i32,
+    pub total: i32,
+    pub worklogs: Vec<WorkLog>,
+}
+
+impl Paginated for ListWorkLogsResponse {
+    type Item = WorkLog;
+
+    fn next_page(&self) -> Option<NextPage> {
+        let next_start_at = self.start_at + self.worklogs.len() as i32;
+        if self.worklogs.is_empty() || next_start_at >= self.total {
+            return None;
+        }
+
+        Some(NextPage::StartAt(next_start_at))
+    }
+
+    fn into_items(self) -> Vec<WorkLog> {
+        self.worklogs
+    }
+}
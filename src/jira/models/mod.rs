@@ -1,12 +1,19 @@
 pub mod board_configuration;
+pub mod changelog;
+pub mod comment;
 pub mod component;
 pub mod epic;
 pub mod issue;
 pub mod list_issues_response;
 pub mod list_sprints_response;
+pub mod list_worklogs_response;
 pub mod priority;
+pub mod project;
 pub mod resolution;
+pub mod search_issues_response;
 pub mod sprint;
 pub mod status;
 pub mod time_tracking;
 pub mod user;
+pub mod watches;
+pub mod worklog;
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub id: String,
+    pub key: String,
+    pub name: String,
+}
@@ -0,0 +1,37 @@
+use crate::http::pagination::{NextPage, Paginated};
+use serde::Deserialize;
+
+/// Response of a JQL search, when no field is requested
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIssuesResponse {
+    pub start_at: i32,
+    #[allow(dead_code)]
+    pub max_results: i32,
+    pub total: i32,
+    pub issues: Vec<IssueReference>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueReference {
+    pub id: String,
+    pub key: String,
+}
+
+impl Paginated for SearchIssuesResponse {
+    type Item = IssueReference;
+
+    fn next_page(&self) -> Option<NextPage> {
+        let next_start_at = self.start_at + self.issues.len() as i32;
+        if self.issues.is_empty() || next_start_at >= self.total {
+            return None;
+        }
+
+        Some(NextPage::StartAt(next_start_at))
+    }
+
+    fn into_items(self) -> Vec<IssueReference> {
+        self.issues
+    }
+}
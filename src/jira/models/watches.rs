@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Watches {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub watch_count: i32,
+    pub is_watching: bool,
+}
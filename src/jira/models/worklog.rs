@@ -0,0 +1,25 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLog {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub id: String,
+    pub issue_id: String,
+    pub author: WorkLogAuthor,
+    pub started: String,
+    pub time_spent_seconds: i32,
+    // Atlassian Document Format
+    pub comment: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLogAuthor {
+    pub account_id: String,
+    #[allow(dead_code)]
+    pub display_name: String,
+}
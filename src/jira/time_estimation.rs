@@ -1,17 +1,16 @@
 use crate::jira::models::priority::PriorityLevel;
+use crate::utils::random;
 use crate::work_event::{ToWorkEvents, WorkEvent, WorkEvents};
 use crate::Issue;
 use chrono::NaiveDate;
 use num_traits::ToPrimitive;
-use rand::{thread_rng, Rng};
 
 impl Issue {
-    pub fn compute_time_score(&self, user_email: &str, date: &NaiveDate) -> f64 {
+    pub fn compute_time_score(&self, date: &NaiveDate) -> f64 {
         let mut score: f64 = self.estimation().unwrap_or(1.0);
 
-        if self.is_assigned_to(user_email) {
-            score *= 2.0;
-        }
+        // Weighted by how the user is involved in the issue (assignee, reporter, ...)
+        score *= self.involvement_weight.unwrap_or(1.0);
 
         if self.is_in_progress() {
             score *= 2.0;
@@ -28,8 +27,13 @@ impl Issue {
             _ => (),
         }
 
+        // Each hour planned in Tempo Planner adds the base score once more
+        if let Some(planned_seconds) = self.planned_seconds {
+            score *= 1.0 + f64::from(planned_seconds) / 3600.0;
+        }
+
         // Add a bit of randomness
-        score *= thread_rng().gen_range(0.7..=1.3);
+        score *= random::gen_range(0.7..=1.3);
 
         score
     }
@@ -40,17 +44,22 @@ impl ToWorkEvents<Issue> for Vec<Issue> {
         self,
         day_duration: &i32,
         increment_duration: &i32,
-        user_email: &str,
+        _user_email: &str,
         date: &NaiveDate,
         _default_issue_key: Option<&String>,
     ) -> WorkEvents<Issue> {
         let mut events = Vec::new();
 
+        // Nothing to share (e.g. when the meetings already fill the day)
+        if *day_duration <= 0 {
+            return events;
+        }
+
         // Compute score for each issue and order them by descending order
         let mut scores: Vec<IssueWithScore> = self
             .into_iter()
             .map(|issue| IssueWithScore {
-                score: issue.compute_time_score(user_email, date),
+                score: issue.compute_time_score(date),
                 issue,
             })
             .collect();
@@ -73,7 +82,7 @@ impl ToWorkEvents<Issue> for Vec<Issue> {
                 time += increment_duration_f64 - (time % increment_duration_f64);
             }
 
-            let mut time_i32 = time.round().to_i32().unwrap();
+            let mut time_i32 = time.round().to_i32().unwrap().max(0);
 
             // Round to at least 1 increment if there is some remaining time
             if time_i32 == 0 && time_sum < *day_duration {
@@ -1,41 +1,111 @@
 mod calendar;
+mod description_template;
 mod http;
 mod jira;
 mod tempo;
 pub mod utils;
 mod work_event;
+mod work_log_sink;
 
+use crate::calendar::absences::find_absence;
+pub use crate::calendar::calendar_cache::CalendarCache;
 use crate::calendar::calendar_parser::CalendarParser;
-use crate::calendar::meetings::FromIcal;
+pub use crate::calendar::calendar_source::{
+    CalendarCredentials, CalendarProtocol, CalendarSource, MeetingFilter,
+};
+pub use crate::calendar::meeting_durations::{MeetingDurations, MeetingRounding};
+pub use crate::calendar::meeting_rule::{MeetingRule, MeetingRuleField};
+use crate::calendar::meetings::{FromIcal, MeetingsOptions};
 use crate::calendar::models::meeting::Meeting;
+use crate::calendar::overlaps::truncate_overlaps;
+pub use crate::calendar::overlaps::{OverlapPreference, OverlapPreferences};
+pub use crate::calendar::overtime::MeetingOvertime;
+use crate::calendar::overtime::{cap_to_remaining_time, clip_to_working_hours};
+pub use crate::calendar::skipped_events::{SkippedEventKind, SkippedEvents};
+pub use crate::calendar::working_hours::WorkingHours;
+pub use crate::description_template::{
+    DescriptionTemplate, ISSUE_DESCRIPTION_FIELDS, MEETING_DESCRIPTION_FIELDS,
+    PLANNED_ISSUE_DESCRIPTION_FIELDS,
+};
+use crate::http::cassette::{redact_email, RecordedRun};
+pub use crate::http::cassette::{Cassette, CassetteMode, Interaction};
+pub use crate::jira::involvement::{Involvement, InvolvementRole, InvolvementRule};
+pub use crate::jira::issue_filter::{IssueFilter, IssueFilters};
 use crate::jira::jira_client::JiraClient;
 use crate::jira::models::issue::Issue;
+use crate::tempo::models::timesheet_approval::TimesheetApproval;
+pub use crate::tempo::planner::PlannerMode;
+use crate::tempo::planner::{cap_planned_time, PlannedIssue};
+pub use crate::tempo::tempo_api_version::TempoApiVersion;
 use crate::tempo::tempo_client::TempoClient;
-use crate::utils::date::format_duration;
-use crate::work_event::{ToWorkEvents, WorkEvents};
+pub use crate::tempo::work_attribute::WorkAttribute;
+use crate::utils::date::{date_to_tempo_format, format_duration};
+use crate::utils::random;
+use crate::work_event::{ToWorkEvents, WorkEvent, WorkEvents};
+use crate::work_log_sink::WorkLogSink;
+pub use crate::work_log_sink::WorkLogSinkKind;
 use chrono::NaiveDate;
+use chrono_tz::Tz;
 use dialoguer::Confirm;
+use ical::parser::ical::component::IcalCalendar;
 use log::Level::Info;
 use log::{info, log_enabled, warn};
+use regex::Regex;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub jira: JiraHttpConfig,
-    pub tempo: TempoHttpConfig,
+    // Optional when the work is logged to Jira, and the day duration is specified
+    pub tempo: Option<TempoHttpConfig>,
+    pub worklog_sink: WorkLogSinkKind,
 
     pub board_id: i32,
     pub sprint_prefix: String,
     pub project_prefixes: Vec<String>,
+    pub issue_filters: IssueFilters,
+    pub involvement: Involvement,
+    // When specified, the Tempo Planner allocations of the day are used
+    pub planner_mode: Option<PlannerMode>,
 
     pub date: NaiveDate,
+    // Local time zone of the user: the calendar events are converted to it, and the work logs are sent in it
+    pub timezone: Tz,
+    // Events spanning several days are clipped to these hours
+    pub working_hours: WorkingHours,
+    // Calendar events not logged as meetings (declined, free, ...)
+    pub skipped_events: SkippedEvents,
+    // Which meeting owns the time where several meetings overlap
+    pub overlap_preferences: OverlapPreferences,
 
     pub target_workday_duration_seconds: Option<i32>,
 
     // Every logged duration will be a multiple of this increment
     pub work_increment_seconds: i32,
 
-    pub calendar_ics: Option<String>,
+    // Calendars whose meetings are logged, merged before removing the overlaps
+    pub calendars: Vec<CalendarSource>,
+    // Local copies of the calendars downloaded from an url, if enabled
+    pub calendar_cache: Option<CalendarCache>,
+    // Used for the meetings without an issue key, before the default issue
+    pub meeting_rules: Vec<MeetingRule>,
     pub meeting_default_issue: Option<String>,
+    // Rounding and minimum duration of the meetings
+    pub meeting_durations: MeetingDurations,
+    // What to do with the meetings exceeding the remaining time of the day
+    pub meeting_overtime: MeetingOvertime,
+    // Descriptions of the work logs, with `{field}` placeholders
+    pub issue_description: DescriptionTemplate,
+    pub meeting_description: DescriptionTemplate,
+    pub planned_issue_description: DescriptionTemplate,
+    pub non_billable_meetings: bool,
+
+    // All-day events matching this pattern are considered as absences
+    pub absence_pattern: Regex,
+    // When specified, the days of absence are logged to this issue (otherwise nothing is logged)
+    pub absence_issue: Option<String>,
+
+    // When specified, the timesheet is submitted to this reviewer after logging the last working day of the period
+    pub timesheet_reviewer: Option<String>,
 
     pub dry_run: bool,
     pub skip_confirmation: bool,
@@ -43,34 +113,104 @@ pub struct Config {
 
 #[derive(Debug, Clone)]
 pub struct JiraHttpConfig {
+    // Either a host name, or a base url (see `HttpClientConfig::new`)
     pub host: String,
     pub email: String,
     pub api_key: String,
+    pub network: NetworkConfig,
 }
 
 #[derive(Debug, Clone)]
 pub struct TempoHttpConfig {
+    // Either a host name, or a base url (see `HttpClientConfig::new`)
+    pub url: String,
+    pub network: NetworkConfig,
     pub api_key: String,
     pub account_id: String,
+    pub api_version: TempoApiVersion,
+    pub work_attributes: Vec<WorkAttribute>,
+    // When specified, the default account of the issue's project is sent as this work attribute
+    pub account_attribute: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+    // HTTP(S) proxy url, used for all the requests
+    pub proxy: Option<String>,
+    // Path to a PEM file with additional root certificates to trust
+    pub ca_bundle: Option<String>,
+    // When specified, the requests are recorded to (or replayed from) this cassette
+    pub cassette: Option<Cassette>,
 }
 
 pub async fn run(config: Config) {
-    let jira_client = JiraClient::new(&config.jira);
-    let tempo_client = TempoClient::new(&config.tempo);
+    random::scope(run_with_randomness(config)).await
+}
+
+async fn run_with_randomness(config: Config) {
+    let config = start_cassette(config);
+    let run_marker = run_marker(&config);
+    let jira_client = JiraClient::new(&config.jira).with_timezone(config.timezone);
+    let tempo_client = config
+        .tempo
+        .as_ref()
+        .map(|tempo_config| TempoClient::new(tempo_config, &config.jira));
+
+    let sink: &dyn WorkLogSink = match config.worklog_sink {
+        WorkLogSinkKind::Tempo => tempo_client
+            .as_ref()
+            .expect("Tempo must be configured to log the work to Tempo"),
+        WorkLogSinkKind::Jira => &jira_client,
+    };
+
+    // A submitted timesheet can't be modified, so check it before logging anything
+    let timesheet_approval = match (&config.timesheet_reviewer, &tempo_client) {
+        (Some(_), Some(client)) => {
+            let approval = client.get_timesheet_approval(&config.date).await;
+            if approval.is_submitted_or_approved() {
+                warn!(
+                    "The timesheet of the period {} - {} is already submitted or approved ({}), exiting.",
+                    approval.period.from, approval.period.to, approval.status.key
+                );
+                return;
+            }
+            Some(approval)
+        }
+        _ => None,
+    };
 
-    let workday_duration_tempo = tempo_client.work_duration(&config.date).await;
-    let workday_duration = config
-        .target_workday_duration_seconds
-        .unwrap_or(workday_duration_tempo);
+    // Nothing is logged on holidays and non-working days
+    let schedule = match &tempo_client {
+        Some(client) => match client.schedule(&config.date).await {
+            Some(schedule) if !schedule.is_working_day() => {
+                info!(
+                    "{} is not a working day ({}), exiting.",
+                    config.date, schedule.type_
+                );
+                return;
+            }
+            Some(schedule) => Some(schedule),
+            None => {
+                warn!("No schedule found in Tempo for {}", config.date);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let workday_duration = match (config.target_workday_duration_seconds, &schedule) {
+        (Some(duration), _) => duration,
+        (None, Some(schedule)) => schedule.required_seconds,
+        (None, None) => {
+            warn!("Unknown duration of the day, exiting.");
+            return;
+        }
+    };
 
     let board_configuration = jira_client.get_board_configuration(config.board_id).await;
     let estimation_field = board_configuration.estimation_field_name();
 
-    let worklogs = tempo_client.list_worklogs(&config.date).await;
-    let already_worked_time = worklogs
-        .iter()
-        .map(|worklog| worklog.time_spent_seconds)
-        .sum::<i32>();
+    let already_worked_time = sink.logged_time(&config.date).await;
     let mut remaining_time = workday_duration - already_worked_time;
 
     if log_enabled!(Info) {
@@ -92,16 +232,69 @@ pub async fn run(config: Config) {
         info!("");
     }
 
-    if remaining_time == 0 {
-        info!("No time left to log, exiting.");
+    if remaining_time <= 0 {
+        info!("No time left to log.");
+        submit_timesheet_if_last_day(&config, tempo_client.as_ref(), timesheet_approval.as_ref())
+            .await;
+        return;
+    }
+
+    let calendars = fetch_calendars(&config).await;
+
+    // On days off, either log the whole day to the absence issue or log nothing
+    if let Some(absence) = calendars.iter().find_map(|(source, calendar)| {
+        find_absence(source, calendar, &config.date, &config.absence_pattern)
+    }) {
+        let absence_issue = match &config.absence_issue {
+            Some(issue) => issue,
+            None => {
+                info!("Absence found ({}), exiting.", absence);
+                return;
+            }
+        };
+
+        info!(
+            "Absence found: {} / time: {}",
+            absence,
+            format_duration(&remaining_time)
+        );
+
+        if confirm_logging(&config) {
+            let absence_event = WorkEvent::new(
+                remaining_time,
+                1.0,
+                absence_issue.to_string(),
+                absence,
+                None,
+                (),
+            );
+            absence_event.log_to(sink, &config.date).await;
+
+            info!("All logged!");
+        } else if !config.dry_run {
+            return;
+        }
+
+        submit_timesheet_if_last_day(&config, tempo_client.as_ref(), timesheet_approval.as_ref())
+            .await;
         return;
     }
 
-    let meetings_events = fetch_meetings_events(&config).await;
+    let mut meetings_events = fetch_meetings_events(&config, &calendars, &run_marker).await;
+    if config.meeting_overtime == MeetingOvertime::Cap {
+        meetings_events = cap_to_remaining_time(meetings_events, remaining_time);
+    }
     remaining_time -= meetings_events
         .iter()
         .map(|event| event.duration)
         .sum::<i32>();
+    if remaining_time < 0 {
+        warn!(
+            "Overtime: the meetings exceed the day by {}",
+            format_duration(&-remaining_time)
+        );
+        remaining_time = 0;
+    }
     if log_enabled!(Info) {
         info!(
             "Remaining time (after meetings): {}",
@@ -109,24 +302,100 @@ pub async fn run(config: Config) {
         );
     }
 
-    let issues_events = fetch_issues_events(
-        &config,
-        &jira_client,
-        estimation_field.as_ref(),
-        &remaining_time,
-    )
-    .await;
+    let mut planned_issues =
+        fetch_planned_issues(&config, tempo_client.as_ref(), &jira_client).await;
+    if config.planner_mode == Some(PlannerMode::Fixed) {
+        planned_issues = cap_planned_time(planned_issues, remaining_time);
+        remaining_time -= planned_issues
+            .iter()
+            .map(|planned_issue| planned_issue.planned_seconds)
+            .sum::<i32>();
+        if log_enabled!(Info) {
+            info!(
+                "Remaining time (after planned issues): {}",
+                format_duration(&remaining_time)
+            );
+        }
+    }
+
+    let issues_events = if remaining_time > 0 {
+        fetch_issues_events(
+            &config,
+            &jira_client,
+            estimation_field.as_ref(),
+            &remaining_time,
+            &planned_issues,
+            &run_marker,
+        )
+        .await
+    } else {
+        info!("No time left for the issues.");
+        WorkEvents::new()
+    };
+
+    let mut planned_events = match config.planner_mode {
+        Some(PlannerMode::Fixed) => planned_issues.to_sorted_events(&0, &0, "", &config.date, None),
+        _ => WorkEvents::new(),
+    };
+    for event in planned_events.iter_mut() {
+        event.description = config
+            .planned_issue_description
+            .render_planned_issue(&event.event, &run_marker);
+    }
 
-    if meetings_events.is_empty() && issues_events.is_empty() {
+    if meetings_events.is_empty() && planned_events.is_empty() && issues_events.is_empty() {
         warn!("No meetings or issues to log for the day, exiting.");
         return;
     }
 
-    if config.dry_run {
-        info!("Dry-run mode, exiting.");
+    if confirm_logging(&config) {
+        info!("Logging your time...");
+
+        for event in meetings_events.iter() {
+            event.log_to(sink, &config.date).await;
+        }
+
+        for event in planned_events.iter() {
+            event.log_to(sink, &config.date).await;
+        }
+
+        for event in issues_events.iter() {
+            event.log_to(sink, &config.date).await;
+        }
+
+        info!("All logged!");
+    } else if !config.dry_run {
         return;
     }
 
+    submit_timesheet_if_last_day(&config, tempo_client.as_ref(), timesheet_approval.as_ref()).await;
+}
+
+/// Identifies the work logs of the run, when a description template uses it.
+/// It's generated with the randomness of the run, so a replayed run gets the same one.
+fn run_marker(config: &Config) -> String {
+    let templates = [
+        &config.issue_description,
+        &config.meeting_description,
+        &config.planned_issue_description,
+    ];
+    if !templates
+        .iter()
+        .any(|template| template.fields().iter().any(|field| field == "run"))
+    {
+        return "".to_string();
+    }
+
+    format!("tempo-bot#{:08x}", random::gen_u32())
+}
+
+/// Checks whether the time should be logged (i.e. not in dry-run mode, and confirmed by the user)
+fn confirm_logging(config: &Config) -> bool {
+    if config.dry_run {
+        info!("Dry-run mode, nothing is logged.");
+        return false;
+    }
+
     if !config.skip_confirmation
         && !Confirm::new()
             .with_prompt("Do you want to log your time, as specified above?")
@@ -135,36 +404,145 @@ pub async fn run(config: Config) {
             .unwrap()
     {
         info!("Exiting.");
+        return false;
+    }
+
+    true
+}
+
+async fn submit_timesheet_if_last_day(
+    config: &Config,
+    tempo_client: Option<&TempoClient>,
+    approval: Option<&TimesheetApproval>,
+) {
+    let (reviewer, client, approval) = match (&config.timesheet_reviewer, tempo_client, approval) {
+        (Some(reviewer), Some(client), Some(approval)) => (reviewer, client, approval),
+        _ => return,
+    };
+
+    if !client
+        .is_last_working_day(&config.date, &approval.period.to)
+        .await
+    {
+        return;
+    }
+
+    if config.dry_run {
+        info!(
+            "Last working day of the period {} - {}, the timesheet would be submitted to {}",
+            approval.period.from, approval.period.to, reviewer
+        );
+        return;
+    }
+
+    // A submitted timesheet can't be modified anymore, so always ask first
+    if !config.skip_confirmation
+        && !Confirm::new()
+            .with_prompt(format!(
+                "Last working day of the period {} - {}. Do you want to submit your timesheet to {}?",
+                approval.period.from, approval.period.to, reviewer
+            ))
+            .wait_for_newline(true)
+            .interact()
+            .unwrap()
+    {
+        info!("Timesheet not submitted.");
         return;
     }
 
-    info!("Logging your time...");
+    let submitted_approval = client.submit_timesheet(approval, reviewer).await;
+    info!(
+        "Timesheet of the period {} - {} submitted for approval ({})",
+        submitted_approval.period.from, submitted_approval.period.to, submitted_approval.status.key
+    );
+}
 
-    for event in meetings_events.iter() {
-        event.log_to_tempo(&tempo_client, &config.date).await;
+/// Saves what's needed to replay the run when recording, and restores it when replaying
+fn start_cassette(mut config: Config) -> Config {
+    if let Some(cassette) = config.jira.network.cassette.clone() {
+        match cassette.mode {
+            CassetteMode::Record => {
+                cassette.start_recording();
+                let seed = random::random_seed();
+                cassette.save_run(&RecordedRun {
+                    email: redact_email(&config.jira.email),
+                    date: config.date,
+                    seed,
+                });
+                random::set_seed(seed);
+                info!("Recording the requests to {}", cassette.dir);
+            }
+            CassetteMode::Replay => {
+                let recorded_run = cassette.load_run();
+                info!(
+                    "Replaying the run of {} recorded in {}",
+                    recorded_run.date, cassette.dir
+                );
+                config.jira.email = recorded_run.email;
+                config.date = recorded_run.date;
+                random::set_seed(recorded_run.seed);
+            }
+        }
     }
 
-    for event in issues_events.iter() {
-        event.log_to_tempo(&tempo_client, &config.date).await;
+    config
+}
+
+async fn fetch_calendars(config: &Config) -> Vec<(CalendarSource, IcalCalendar)> {
+    let mut calendars = Vec::new();
+    for (index, source) in config.calendars.iter().enumerate() {
+        if let Some(calendar) = CalendarParser::new(source, index, &config.jira.network)
+            .with_cache(config.calendar_cache.as_ref())
+            .parse(&config.date, &config.timezone)
+            .await
+        {
+            calendars.push((source.clone(), calendar));
+        }
     }
 
-    info!("All logged!");
+    calendars
 }
 
 // TODO: ignore meetings already logged in tempo
-async fn fetch_meetings_events(config: &Config) -> WorkEvents<Meeting> {
-    if let Some(ics_file) = &config.calendar_ics {
-        let parser = CalendarParser::new(ics_file);
-        let calendar = parser.parse().await.unwrap();
-        let meetings =
-            Vec::<Meeting>::from_icalendar(&calendar, &config.date, &config.project_prefixes, true);
+async fn fetch_meetings_events(
+    config: &Config,
+    calendars: &[(CalendarSource, IcalCalendar)],
+    run_marker: &str,
+) -> WorkEvents<Meeting> {
+    if !calendars.is_empty() {
+        let meetings = Vec::<Meeting>::from_icalendar(
+            calendars,
+            &config.date,
+            &config.project_prefixes,
+            true,
+            &MeetingsOptions {
+                timezone: config.timezone,
+                working_hours: config.working_hours,
+                user_email: config.jira.email.to_string(),
+                skipped_events: config.skipped_events.clone(),
+                overlap_preferences: config.overlap_preferences.clone(),
+                rules: config.meeting_rules.clone(),
+            },
+        );
+
+        let meetings = match config.meeting_overtime {
+            MeetingOvertime::WorkingHours => {
+                clip_to_working_hours(meetings, &config.working_hours, &config.date)
+            }
+            _ => meetings,
+        };
+        // The merged short meetings and the rounded durations can overlap the next meetings
+        let meetings = truncate_overlaps(
+            config.meeting_durations.apply(meetings),
+            &config.overlap_preferences,
+        );
 
         if meetings.is_empty() {
             info!("No meeting found for the day.");
             return WorkEvents::new();
         }
 
-        let events = meetings.to_sorted_events(
+        let mut events = meetings.to_sorted_events(
             &0,
             &0,
             "",
@@ -172,6 +550,16 @@ async fn fetch_meetings_events(config: &Config) -> WorkEvents<Meeting> {
             config.meeting_default_issue.as_ref(),
         );
 
+        for event in events.iter_mut() {
+            event.description =
+                config
+                    .meeting_description
+                    .render_meeting(&event.event, &event.key, run_marker);
+            if config.non_billable_meetings {
+                event.billable = false;
+            }
+        }
+
         if log_enabled!(Info) {
             info!("Found {} meetings:", events.len());
             for event in events.iter() {
@@ -190,11 +578,77 @@ async fn fetch_meetings_events(config: &Config) -> WorkEvents<Meeting> {
     WorkEvents::new()
 }
 
+async fn fetch_planned_issues(
+    config: &Config,
+    tempo_client: Option<&TempoClient>,
+    jira_client: &JiraClient,
+) -> Vec<PlannedIssue> {
+    let client = match (config.planner_mode, tempo_client) {
+        (Some(_), Some(client)) => client,
+        _ => return Vec::new(),
+    };
+
+    let date_as_str = date_to_tempo_format(&config.date);
+    let mut planned_issues: Vec<PlannedIssue> = Vec::new();
+
+    for plan in client.list_plans(&config.date).await {
+        let issue_id = match plan.issue_id() {
+            Some(issue_id) => issue_id,
+            None => {
+                info!(
+                    "Skipping plan {} ({} plans are not supported)",
+                    plan.id, plan.plan_item.type_
+                );
+                continue;
+            }
+        };
+
+        let planned_seconds = plan.planned_seconds_on(&date_as_str);
+        if planned_seconds <= 0 {
+            continue;
+        }
+
+        let key = jira_client.get_issue_reference(&issue_id).await.key;
+
+        // Several plans can target the same issue
+        match planned_issues
+            .iter_mut()
+            .find(|planned_issue| planned_issue.key == key)
+        {
+            Some(planned_issue) => planned_issue.planned_seconds += planned_seconds,
+            None => planned_issues.push(PlannedIssue {
+                key,
+                planned_seconds,
+                description: plan.description.clone().unwrap_or_default(),
+            }),
+        }
+    }
+
+    if log_enabled!(Info) {
+        if planned_issues.is_empty() {
+            info!("No planned issue found for the day.");
+        } else {
+            info!("Found {} planned issues:", planned_issues.len());
+            for planned_issue in planned_issues.iter() {
+                info!(
+                    "- {} / planned time: {}",
+                    planned_issue.key,
+                    format_duration(&planned_issue.planned_seconds)
+                );
+            }
+        }
+    }
+
+    planned_issues
+}
+
 async fn fetch_issues_events(
     config: &Config,
     jira_client: &JiraClient,
     estimation_field: Option<&String>,
     remaining_time: &i32,
+    planned_issues: &[PlannedIssue],
+    run_marker: &str,
 ) -> WorkEvents<Issue> {
     let sprint = jira_client
         .first_active_sprint_for_prefix(config.board_id, config.sprint_prefix.as_str())
@@ -204,21 +658,57 @@ async fn fetch_issues_events(
     info!("Found current sprint: {}", sprint.name);
 
     let issues = jira_client
-        .list_issues_in_sprint(sprint.id, estimation_field, true)
+        .list_issues_in_sprint(
+            sprint.id,
+            estimation_field,
+            Some(&config.involvement),
+            &config.date,
+        )
         .await;
 
+    let (mut issues, dropped_issues) = config.issue_filters.apply(issues);
+    if log_enabled!(Info) && !dropped_issues.is_empty() {
+        info!("Skipped issues:");
+        for (issue, reason) in dropped_issues.iter() {
+            info!("- {}: {} ({})", issue.key, issue.fields.summary, reason);
+        }
+    }
+
+    let planned_seconds = |issue: &Issue| {
+        planned_issues
+            .iter()
+            .find(|planned_issue| planned_issue.key == issue.key)
+            .map(|planned_issue| planned_issue.planned_seconds)
+    };
+    match config.planner_mode {
+        // The planned issues are already logged with their planned time
+        Some(PlannerMode::Fixed) => issues.retain(|issue| planned_seconds(issue).is_none()),
+        Some(PlannerMode::Prior) => {
+            for issue in issues.iter_mut() {
+                issue.planned_seconds = planned_seconds(issue);
+            }
+        }
+        None => {}
+    }
+
     if issues.is_empty() {
         info!("No issue found for the user.");
         return WorkEvents::new();
     }
 
-    let issues_events = issues.to_sorted_events(
+    let mut issues_events = issues.to_sorted_events(
         remaining_time,
         &config.work_increment_seconds,
         &config.jira.email,
         &config.date,
         config.meeting_default_issue.as_ref(),
     );
+    for event in issues_events.iter_mut() {
+        event.description =
+            config
+                .issue_description
+                .render_issue(&event.event, &sprint, run_marker);
+    }
 
     if log_enabled!(Info) {
         info!("Issues w/ scores & assigned times :");
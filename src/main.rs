@@ -1,9 +1,18 @@
-use clap::Parser;
+use chrono::Duration;
+use chrono_tz::Tz;
+use clap::{ErrorKind, IntoApp, Parser};
 use env_logger::Env;
 use log::Level::Info;
 use log::{info, log_enabled};
-use tempo_bot::utils::date::parse_date_from_str;
-use tempo_bot::{run, Config, JiraHttpConfig, TempoHttpConfig};
+use regex::Regex;
+use tempo_bot::utils::date::{format_duration, parse_date_from_str};
+use tempo_bot::{
+    run, CalendarCache, CalendarSource, Cassette, CassetteMode, Config, DescriptionTemplate,
+    Involvement, InvolvementRule, IssueFilter, IssueFilters, JiraHttpConfig, MeetingDurations,
+    MeetingOvertime, MeetingRounding, MeetingRule, NetworkConfig, OverlapPreferences, PlannerMode,
+    SkippedEvents, TempoApiVersion, TempoHttpConfig, WorkAttribute, WorkLogSinkKind, WorkingHours,
+    ISSUE_DESCRIPTION_FIELDS, MEETING_DESCRIPTION_FIELDS, PLANNED_ISSUE_DESCRIPTION_FIELDS,
+};
 
 #[derive(Parser)]
 #[clap(
@@ -12,7 +21,8 @@ use tempo_bot::{run, Config, JiraHttpConfig, TempoHttpConfig};
     about = "Log your time on Tempo automatically, based on your current tickets in Jira."
 )]
 pub struct Opts {
-    /// The Jira host name. If you are using Jira Cloud, it's likely '<company>.atlassian.net'
+    /// The Jira host name. If you are using Jira Cloud, it's likely '<company>.atlassian.net'.
+    /// A full base url can also be used, e.g. 'http://localhost:8080' or 'https://gateway.company.com/jira'
     #[clap(short, long)]
     jira_host: String,
     /// The Board ID where your sprints live. Can be found in the url of your backlog/sprint page
@@ -26,10 +36,39 @@ pub struct Opts {
     #[clap(short, long)]
     project_prefixes: String,
 
+    /// Only keep the issues matching this filter. Format: '<field>=<value>', with field being one of
+    /// 'type', 'label', 'component', 'status', 'status-category' or 'epic' (epic key).
+    /// Can be repeated: filters on the same field are OR-ed, filters on different fields are AND-ed
+    #[clap(long = "include-issue")]
+    include_issues: Vec<IssueFilter>,
+    /// Skip the issues matching this filter (same format as --include-issue). Can be repeated
+    #[clap(long = "exclude-issue")]
+    exclude_issues: Vec<IssueFilter>,
+
+    /// How being involved in an issue keeps it and weights its time score. Format: '<role>=<weight>' (a positive
+    /// number) or '<role>=off',
+    /// with role being one of 'assignee', 'reporter', 'watcher', 'commenter' or 'previous-assignee'.
+    /// Can be repeated. By default, only 'assignee=2' and 'reporter=1' are enabled
+    #[clap(long = "involvement")]
+    involvement: Vec<InvolvementRule>,
+    /// Number of days a comment is considered recent, for the 'commenter' involvement role
+    #[clap(long, default_value = "7")]
+    recent_comment_days: i64,
+
+    /// Use your Tempo Planner allocations of the day: 'fixed' logs exactly the planned time (the unplanned time is
+    /// allocated to the sprint issues), 'prior' boosts the time score of the planned sprint issues.
+    /// If not specified, the plans are ignored
+    #[clap(long)]
+    planner_mode: Option<PlannerMode>,
+
     /// The date to log time for. Accepted formats: 'today', 'yesterday', 'YYYY-MM-DD'
     #[clap(short, long)]
     date: String,
 
+    /// Your time zone, e.g. 'Europe/Paris'. Used for 'today' and 'yesterday', and to convert the calendar events
+    #[clap(long, default_value = "UTC")]
+    timezone: Tz,
+
     /// Email (used to login in Jira)
     #[clap(short, long)]
     email: String,
@@ -37,12 +76,33 @@ pub struct Opts {
     #[clap(long)]
     api_key: String,
 
-    /// Tempo API key. Can be generated from "Tempo > Settings > API integration" in your dashboard
+    /// Tempo API key. Can be generated from "Tempo > Settings > API integration" in your dashboard.
+    /// Required when logging to Tempo
     #[clap(long)]
-    tempo_api_key: String,
+    tempo_api_key: Option<String>,
     /// User's account id (used to login in Tempo). Can be found in the url of your profile page.
+    /// Required when logging to Tempo
     #[clap(short, long)]
-    account_id: String,
+    account_id: Option<String>,
+    /// Base url of the Tempo API. For the EU region, use 'https://api.eu.tempo.io'.
+    /// A host name, a local url (e.g. 'http://localhost:8080') or a url with a path are also accepted
+    #[clap(long, default_value = "https://api.tempo.io")]
+    tempo_url: String,
+    /// Version of the Tempo REST API to use: '3' (deprecated) or '4'
+    #[clap(long, default_value = "3")]
+    tempo_api_version: TempoApiVersion,
+    /// Tempo work attribute to send with the work logs. Format: '[<scope>:]<key>=<value>', where the optional scope is
+    /// either a project prefix (e.g. 'ABC') or an issue key (e.g. 'ABC-123'). Can be repeated: the most specific scope wins
+    #[clap(long = "work-attribute")]
+    work_attributes: Vec<WorkAttribute>,
+    /// Key of the Tempo work attribute holding the account (usually '_Account_'). If specified, the default account
+    /// of the issue's project is sent, unless set with --work-attribute
+    #[clap(long)]
+    account_attribute: Option<String>,
+
+    /// Where to log the time: 'tempo', or 'jira' (native Jira work logs, when Tempo is not available)
+    #[clap(long, default_value = "tempo")]
+    worklog_sink: WorkLogSinkKind,
 
     /// Target working day duration (in hours). If not specified, the default day duration from Tempo is used.
     #[clap(long)]
@@ -54,15 +114,133 @@ pub struct Opts {
 
     /// Optional ICS calendar file to parse meeting from. If specified, all meeting of the day will be logged in Tempo.
     /// The issue key will be searched in the title and the description of the events, using the prefixes from --project-prefixes.
-    /// Could be either a local file or an url (recommended for real-time calendar updates)
+    /// Could be either a local file or an url (recommended for real-time calendar updates), or a CalDAV calendar url
+    /// prefixed by 'caldav+' (e.g. 'caldav+https://cloud.company.com/remote.php/dav/calendars/john/personal/').
+    /// Can be repeated to merge several calendars, each with its own options separated by ';':
+    /// 'default-issue=<issue key>', 'include=<field>:<regex>' and 'exclude=<field>:<regex>' (with the fields of
+    /// --meeting-rule, both repeatable), and 'user=<user>;password=<password>' or 'token=<token>' to download the url
+    /// ('password-env=<variable>' and 'token-env=<variable>' read them from environment variables). A ';' inside a
+    /// value is escaped as '\;'
     #[clap(short, long)]
-    calendar_ics: Option<String>,
+    calendar_ics: Vec<CalendarSource>,
+
+    /// Directory of the local copies of the calendars downloaded from an url. They're only downloaded again when they
+    /// changed, and used when the download fails. Defaults to the cache directory of the user
+    #[clap(long)]
+    calendar_cache_dir: Option<String>,
+
+    /// How old (in hours) the local copy of a calendar can be, to be used when the calendar can't be downloaded
+    #[clap(long, default_value = "24")]
+    calendar_max_staleness: i64,
+
+    /// Always download the calendars, without keeping any local copy
+    #[clap(long, conflicts_with = "calendar-cache-dir")]
+    no_calendar_cache: bool,
+
+    /// Your working hours, e.g. '08:30-17:30'. Calendar events spanning several days are clipped to these hours
+    #[clap(long, default_value = "09:00-18:00")]
+    working_hours: WorkingHours,
+
+    /// Comma separated kinds of calendar events not to log: declined, tentative, needs-action (not answered yet),
+    /// free (e.g. focus time) and private. Use 'none' to log every event
+    #[clap(long, default_value = "declined,free")]
+    skip_meetings: SkippedEvents,
+
+    /// Comma separated rules to choose which meeting owns the time where meetings overlap (the other ones are
+    /// truncated): earlier, later, shorter, longer, issue-key (has an issue key) and organizer (organized by you).
+    /// The next rules break the ties of the previous ones
+    #[clap(long, default_value = "longer,earlier")]
+    overlap_preference: OverlapPreferences,
+
+    /// Rule mapping the meetings without an issue key in their title or description to an issue, with the format
+    /// '<field>:<regex>=<issue key>' (e.g. 'title:(?i)sprint planning=ABC-1' or 'attendee-domain:^client\.com$=CLI-42').
+    /// Fields: title, description, location, organizer, category and attendee-domain. Can be repeated, the first
+    /// matching rule is used
+    #[clap(long = "meeting-rule")]
+    meeting_rules: Vec<MeetingRule>,
 
     /// Default issue key to use for events from the calendar (when no issue key if found in the titles and description)
     /// If not specified, events without an issue key will be skipped
     #[clap(short, long)]
     meeting_default_issue: Option<String>,
 
+    /// How the durations of the meetings are rounded: none (exact durations), nearest or up. They're rounded to
+    /// multiples of --meeting-increment
+    #[clap(long, default_value = "none")]
+    meeting_rounding: MeetingRounding,
+
+    /// Increment (in minutes) of the rounded meeting durations. Defaults to --work-increment
+    #[clap(long)]
+    meeting_increment: Option<i32>,
+
+    /// Minimum duration of a meeting (in minutes). Shorter meetings are merged into --misc-meetings-issue, or not
+    /// logged at all
+    #[clap(long, default_value = "0")]
+    meeting_min_duration: i32,
+
+    /// Issue key of the meetings shorter than --meeting-min-duration, all logged together
+    #[clap(long)]
+    misc_meetings_issue: Option<String>,
+
+    /// What to do when the meetings exceed the remaining time of the day: log (every meeting is logged, the overtime
+    /// is reported and no time is left for the issues), cap (the latest meetings are shortened or skipped so the day
+    /// isn't exceeded) or working-hours (only the parts of the meetings within --working-hours are logged)
+    #[clap(long, default_value = "log")]
+    meeting_overtime: MeetingOvertime,
+
+    /// Description of the issue work logs, with the placeholders {key}, {summary}, {status}, {type}, {priority}, {epic},
+    /// {labels}, {components}, {sprint} and {run} (marker of the run), e.g. '{summary} [{status}]'
+    #[clap(long, default_value = "")]
+    issue_description: DescriptionTemplate,
+
+    /// Description of the meeting work logs, with the placeholders {key}, {title}, {location}, {organizer},
+    /// {attendees} (number of attendees), {categories}, {start}, {end} and {run}, e.g. 'Meeting: {title} ({attendees} people)'
+    #[clap(long, default_value = "{title}")]
+    meeting_description: DescriptionTemplate,
+
+    /// Description of the work logs of the issues planned in Tempo Planner (with --planner-mode fixed), with the
+    /// placeholders {key}, {description} (of the plan) and {run}
+    #[clap(long, default_value = "{description}")]
+    planned_issue_description: DescriptionTemplate,
+
+    /// Regex matched against the title of all-day events, to detect absences (events flagged as "out of office"
+    /// by Outlook are always absences). On days of absence, either nothing is logged or the day is logged to --absence-issue
+    #[clap(
+        long,
+        default_value = r"(?i)(\bOOO\b|out of office|vacation|holiday|day off|\bleave\b)"
+    )]
+    absence_pattern: Regex,
+
+    /// Issue key to log the days of absence to. If not specified, nothing is logged on days of absence
+    #[clap(long)]
+    absence_issue: Option<String>,
+
+    /// Log the meetings as non-billable (0 billable seconds) in Tempo. Not available with the Jira work log sink
+    #[clap(long)]
+    non_billable_meetings: bool,
+
+    /// Account id of the reviewer to submit the Tempo timesheet to. If specified, the timesheet is submitted after
+    /// logging the last working day of the period, and nothing is logged into already submitted or approved periods
+    #[clap(long)]
+    submit_timesheet_to: Option<String>,
+
+    /// HTTP(S) proxy to use for all the requests (Jira, Tempo and calendars), e.g. 'http://proxy.company.com:3128'
+    #[clap(long)]
+    proxy: Option<String>,
+    /// Path to a PEM file with additional CA certificates to trust (e.g. for a corporate proxy or gateway)
+    #[clap(long)]
+    ca_bundle: Option<String>,
+
+    /// Records every request and response to this new (or empty) directory (without the credentials, and with the emails
+    /// redacted),
+    /// so the run can be reproduced on another machine with '--replay'
+    #[clap(long, conflicts_with = "replay")]
+    record: Option<String>,
+    /// Replays offline a run recorded with '--record' into this directory. The date and email of the recorded run are
+    /// used, and nothing is sent to Jira or Tempo
+    #[clap(long)]
+    replay: Option<String>,
+
     /// Dry run mode. If specified, no time will be logged
     #[clap(long)]
     dry_run: bool,
@@ -79,16 +257,188 @@ async fn main() {
 
     let options: Opts = Opts::parse();
 
-    let date = parse_date_from_str(options.date.as_str());
+    let network = NetworkConfig {
+        proxy: options.proxy.clone(),
+        ca_bundle: options.ca_bundle.clone(),
+        cassette: match (&options.record, &options.replay) {
+            (Some(dir), _) => Some(Cassette::new(CassetteMode::Record, dir)),
+            (_, Some(dir)) => Some(Cassette::new(CassetteMode::Replay, dir)),
+            _ => None,
+        },
+    };
+
+    let tempo = match (&options.tempo_api_key, &options.account_id) {
+        (Some(api_key), Some(account_id)) => Some(TempoHttpConfig {
+            url: options.tempo_url.to_string(),
+            network: network.clone(),
+            api_key: api_key.to_string(),
+            account_id: account_id.to_string(),
+            api_version: options.tempo_api_version,
+            work_attributes: options.work_attributes.clone(),
+            account_attribute: options.account_attribute.clone(),
+        }),
+        (None, None) => None,
+        _ => Opts::into_app()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--tempo-api-key and --account-id must be specified together",
+            )
+            .exit(),
+    };
+    if tempo.is_none() && options.worklog_sink == WorkLogSinkKind::Tempo {
+        Opts::into_app()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--tempo-api-key and --account-id are required to log the time to Tempo",
+            )
+            .exit();
+    }
+    // Jira work logs have no billable time
+    if options.non_billable_meetings && options.worklog_sink == WorkLogSinkKind::Jira {
+        Opts::into_app()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--non-billable-meetings can only be used with the Tempo work log sink",
+            )
+            .exit();
+    }
+    if tempo.is_none() && options.submit_timesheet_to.is_some() {
+        Opts::into_app()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--tempo-api-key and --account-id are required to submit the timesheet",
+            )
+            .exit();
+    }
+    if tempo.is_none() && options.planner_mode.is_some() {
+        Opts::into_app()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--tempo-api-key and --account-id are required to use the Tempo Planner",
+            )
+            .exit();
+    }
+    if tempo.is_none() && options.day_duration.is_none() {
+        Opts::into_app()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--day-duration is required when Tempo is not configured",
+            )
+            .exit();
+    }
+
+    for (option, template, fields) in [
+        (
+            "--issue-description",
+            &options.issue_description,
+            ISSUE_DESCRIPTION_FIELDS,
+        ),
+        (
+            "--meeting-description",
+            &options.meeting_description,
+            MEETING_DESCRIPTION_FIELDS,
+        ),
+        (
+            "--planned-issue-description",
+            &options.planned_issue_description,
+            PLANNED_ISSUE_DESCRIPTION_FIELDS,
+        ),
+    ] {
+        let unknown_fields = template.unknown_fields(fields);
+        if !unknown_fields.is_empty() {
+            Opts::into_app()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!(
+                        "Unknown placeholders in {}: {{{}}}. Expected: {{{}}}",
+                        option,
+                        unknown_fields.join("}, {"),
+                        fields.join("}, {")
+                    ),
+                )
+                .exit();
+        }
+    }
+
+    let date = parse_date_from_str(options.date.as_str(), &options.timezone);
     let project_prefixes: Vec<String> = options
         .project_prefixes
         .split(',')
         .map(|x| x.to_string())
         .collect();
+    let involvement = Involvement {
+        recent_comment_days: options.recent_comment_days,
+        ..Involvement::default()
+    }
+    .with_rules(&options.involvement);
+
+    let meeting_durations = MeetingDurations {
+        rounding: options.meeting_rounding,
+        increment_seconds: options.meeting_increment.unwrap_or(options.work_increment) * 60,
+        min_duration_seconds: options.meeting_min_duration * 60,
+        misc_issue: options.misc_meetings_issue.clone(),
+    };
+
+    let calendar_cache = match options.no_calendar_cache {
+        true => None,
+        false => options
+            .calendar_cache_dir
+            .clone()
+            .or_else(CalendarCache::default_dir)
+            .map(|dir| CalendarCache {
+                dir,
+                max_staleness: Duration::hours(options.calendar_max_staleness),
+            }),
+    };
 
     if log_enabled!(Info) {
         let display_none_str = "-".to_string();
-        let display_calendar_ics = options.calendar_ics.as_ref().unwrap_or(&display_none_str);
+        let display_calendar_cache = match &calendar_cache {
+            Some(cache) => format!(
+                "{} (max staleness: {}h)",
+                cache.dir, options.calendar_max_staleness
+            ),
+            None => display_none_str.to_string(),
+        };
+        let display_calendar_ics = match options.calendar_ics.is_empty() {
+            true => display_none_str.to_string(),
+            false => options
+                .calendar_ics
+                .iter()
+                .map(|source| source.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        let display_include_issues = options
+            .include_issues
+            .iter()
+            .map(|filter| filter.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let display_exclude_issues = options
+            .exclude_issues
+            .iter()
+            .map(|filter| filter.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let display_involvement = involvement
+            .rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let display_work_attributes = options
+            .work_attributes
+            .iter()
+            .map(|attribute| attribute.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let display_meeting_rules = options
+            .meeting_rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
         let display_default_meeting_issue = options
             .meeting_default_issue
             .as_ref()
@@ -98,13 +448,82 @@ async fn main() {
         info!("Board ID             : {}", options.board_id);
         info!("Sprint prefix        : {}", options.sprint_prefix);
         info!("Project prefixes     : {}", project_prefixes.join(", "));
+        info!("Include issues       : {}", display_include_issues);
+        info!("Exclude issues       : {}", display_exclude_issues);
+        info!("Involvement          : {}", display_involvement);
+        info!(
+            "Planner mode         : {}",
+            options
+                .planner_mode
+                .map(|mode| mode.to_string())
+                .unwrap_or_else(|| display_none_str.to_string())
+        );
         info!("Date                 : {}", date);
+        info!("Time zone            : {}", options.timezone);
         info!("Email                : {}", options.email);
-        info!("Account ID           : {}", options.account_id);
+        info!(
+            "Account ID           : {}",
+            options.account_id.as_ref().unwrap_or(&display_none_str)
+        );
         info!("API Key              : *****");
         info!("API Key for Tempo    : *****");
+        info!("Tempo URL            : {}", options.tempo_url);
+        info!("Tempo API version    : {}", options.tempo_api_version);
+        info!(
+            "Proxy                : {}",
+            options.proxy.as_ref().unwrap_or(&display_none_str)
+        );
+        info!(
+            "CA bundle            : {}",
+            options.ca_bundle.as_ref().unwrap_or(&display_none_str)
+        );
+        info!("Work log sink        : {}", options.worklog_sink);
+        info!("Work attributes      : {}", display_work_attributes);
+        info!(
+            "Account attribute    : {}",
+            options
+                .account_attribute
+                .as_ref()
+                .unwrap_or(&display_none_str)
+        );
+        info!(
+            "Timesheet reviewer   : {}",
+            options
+                .submit_timesheet_to
+                .as_ref()
+                .unwrap_or(&display_none_str)
+        );
         info!("Calendar ICS         : {}", display_calendar_ics);
+        info!("Calendar cache       : {}", display_calendar_cache);
+        info!("Working hours        : {}", options.working_hours);
+        info!("Skipped meetings     : {}", options.skip_meetings);
+        info!("Overlap preference   : {}", options.overlap_preference);
+        info!("Meeting rules        : {}", display_meeting_rules);
         info!("Default meeting issue: {}", display_default_meeting_issue);
+        info!(
+            "Meeting rounding     : {} (increment: {})",
+            meeting_durations.rounding,
+            format_duration(&meeting_durations.increment_seconds)
+        );
+        info!("Meeting overtime     : {}", options.meeting_overtime);
+        info!("Issue description    : {}", options.issue_description);
+        info!("Meeting description  : {}", options.meeting_description);
+        info!(
+            "Planned description  : {}",
+            options.planned_issue_description
+        );
+        info!(
+            "Min meeting duration : {} (misc issue: {})",
+            format_duration(&meeting_durations.min_duration_seconds),
+            meeting_durations
+                .misc_issue
+                .as_ref()
+                .unwrap_or(&display_none_str)
+        );
+        info!(
+            "Absence issue        : {}",
+            options.absence_issue.as_ref().unwrap_or(&display_none_str)
+        );
         info!("-------------------------");
         info!("Work increment: {}m", options.work_increment);
         info!("");
@@ -115,19 +534,39 @@ async fn main() {
             host: options.jira_host,
             email: options.email,
             api_key: options.api_key,
+            network,
         },
-        tempo: TempoHttpConfig {
-            api_key: options.tempo_api_key,
-            account_id: options.account_id,
-        },
+        tempo,
+        worklog_sink: options.worklog_sink,
         board_id: options.board_id,
         sprint_prefix: options.sprint_prefix,
         project_prefixes,
+        issue_filters: IssueFilters {
+            include: options.include_issues,
+            exclude: options.exclude_issues,
+        },
+        involvement,
+        planner_mode: options.planner_mode,
         date,
+        timezone: options.timezone,
+        working_hours: options.working_hours,
+        skipped_events: options.skip_meetings,
+        overlap_preferences: options.overlap_preference,
         target_workday_duration_seconds: options.day_duration.map(|x| x * 3600),
         work_increment_seconds: options.work_increment * 60,
-        calendar_ics: options.calendar_ics,
+        calendars: options.calendar_ics,
+        calendar_cache,
+        meeting_rules: options.meeting_rules,
         meeting_default_issue: options.meeting_default_issue,
+        meeting_durations,
+        meeting_overtime: options.meeting_overtime,
+        issue_description: options.issue_description,
+        meeting_description: options.meeting_description,
+        planned_issue_description: options.planned_issue_description,
+        non_billable_meetings: options.non_billable_meetings,
+        absence_pattern: options.absence_pattern,
+        absence_issue: options.absence_issue,
+        timesheet_reviewer: options.submit_timesheet_to,
         dry_run: options.dry_run,
         skip_confirmation: options.yes,
     };
@@ -1,2 +1,5 @@
 pub mod models;
+pub mod planner;
+pub mod tempo_api_version;
 pub mod tempo_client;
+pub mod work_attribute;
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub key: String,
+    pub id: i32,
+    pub name: String,
+    pub status: String,
+}
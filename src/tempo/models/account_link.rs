@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAccountLinksResponse {
+    #[serde(rename = "self")]
+    #[allow(dead_code)]
+    pub self_: String,
+    pub results: Vec<AccountLink>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLink {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub id: i32,
+    pub scope: AccountLinkScope,
+    pub account: AccountReference,
+    pub default: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLinkScope {
+    pub id: i32,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+// The v3 API returns the account key, the v4 API only returns its id
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountReference {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub key: Option<String>,
+    #[allow(dead_code)]
+    pub id: Option<i32>,
+}
@@ -0,0 +1,33 @@
+use crate::http::pagination::{NextPage, Paginated};
+use crate::tempo::models::plan::Plan;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPlansResponse {
+    #[serde(rename = "self")]
+    #[allow(dead_code)]
+    pub self_: String,
+    pub metadata: ListPlansResponseMetadata,
+    pub results: Vec<Plan>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPlansResponseMetadata {
+    #[allow(dead_code)]
+    pub count: i32,
+    pub next: Option<String>,
+}
+
+impl Paginated for ListPlansResponse {
+    type Item = Plan;
+
+    fn next_page(&self) -> Option<NextPage> {
+        self.metadata.next.clone().map(NextPage::Url)
+    }
+
+    fn into_items(self) -> Vec<Plan> {
+        self.results
+    }
+}
@@ -1,6 +1,7 @@
 use crate::tempo::models::schedule::Schedule;
 use serde::Deserialize;
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ListSchedulesResponse {
@@ -13,5 +14,6 @@ pub struct ListSchedulesResponse {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ListSchedulesResponseMetadata {
+    #[allow(dead_code)]
     pub count: i32,
 }
@@ -1,3 +1,4 @@
+use crate::http::pagination::{NextPage, Paginated};
 use crate::tempo::models::worklog::WorkLog;
 use serde::Deserialize;
 
@@ -5,15 +6,31 @@ use serde::Deserialize;
 #[serde(rename_all = "camelCase")]
 pub struct ListWorkLogsResponse {
     #[serde(rename = "self")]
+    #[allow(dead_code)]
     pub self_: String,
     pub metadata: ListWorkLogsResponseMetadata,
     pub results: Vec<WorkLog>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ListWorkLogsResponseMetadata {
     pub count: i32,
     pub offset: i32,
     pub limit: i32,
+    pub next: Option<String>,
+    pub previous: Option<String>,
+}
+
+impl Paginated for ListWorkLogsResponse {
+    type Item = WorkLog;
+
+    fn next_page(&self) -> Option<NextPage> {
+        self.metadata.next.clone().map(NextPage::Url)
+    }
+
+    fn into_items(self) -> Vec<WorkLog> {
+        self.results
+    }
 }
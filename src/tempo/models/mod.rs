@@ -1,4 +1,9 @@
+pub mod account;
+pub mod account_link;
+pub mod list_plans_response;
 pub mod list_schedules_response;
 pub mod list_worklogs_response;
+pub mod plan;
 pub mod schedule;
+pub mod timesheet_approval;
 pub mod worklog;
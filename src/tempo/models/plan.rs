@@ -0,0 +1,73 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Plan {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub id: i32,
+    pub start_date: String,
+    pub end_date: String,
+    pub seconds_per_day: i32,
+    pub include_non_working_days: bool,
+    pub description: Option<String>,
+    pub plan_item: PlanItem,
+    pub dates: Option<PlanDates>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanItem {
+    #[serde(rename = "self")]
+    pub self_: String,
+    // Not returned by the v3 API, use the "self" url instead
+    pub id: Option<String>,
+    // "ISSUE" or "PROJECT"
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDates {
+    pub values: Vec<PlanDate>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDate {
+    pub date: String,
+    pub time_planned_seconds: i32,
+}
+
+impl Plan {
+    /// Id of the planned Jira issue, or None for the other kinds of plan (e.g. a project)
+    pub fn issue_id(&self) -> Option<String> {
+        if self.plan_item.type_ != "ISSUE" {
+            return None;
+        }
+
+        self.plan_item.id.clone().or_else(|| {
+            self.plan_item
+                .self_
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .map(|id| id.to_string())
+        })
+    }
+
+    /// Time planned on the date, or the default time per day when the plan has no detail per date
+    pub fn planned_seconds_on(&self, date_as_str: &str) -> i32 {
+        match &self.dates {
+            Some(dates) => dates
+                .values
+                .iter()
+                .filter(|plan_date| plan_date.date == date_as_str)
+                .map(|plan_date| plan_date.time_planned_seconds)
+                .sum(),
+            None => self.seconds_per_day,
+        }
+    }
+}
@@ -3,8 +3,16 @@ use serde::Deserialize;
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Schedule {
+    #[allow(dead_code)]
     pub date: String,
     pub required_seconds: i32,
+    // "WORKING_DAY", "NON_WORKING_DAY", "HOLIDAY" or "HOLIDAY_AND_NON_WORKING_DAY"
     #[serde(rename = "type")]
     pub type_: String,
 }
+
+impl Schedule {
+    pub fn is_working_day(&self) -> bool {
+        self.type_ == "WORKING_DAY" && self.required_seconds > 0
+    }
+}
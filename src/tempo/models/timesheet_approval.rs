@@ -0,0 +1,37 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetApproval {
+    #[serde(rename = "self")]
+    pub self_: String,
+    pub period: TimesheetApprovalPeriod,
+    pub required_seconds: i32,
+    pub time_spent_seconds: i32,
+    pub status: TimesheetApprovalStatus,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetApprovalPeriod {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimesheetApprovalStatus {
+    // "OPEN", "IN_REVIEW" or "APPROVED"
+    pub key: String,
+    pub comment: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl TimesheetApproval {
+    pub fn is_submitted_or_approved(&self) -> bool {
+        self.status.key == "IN_REVIEW" || self.status.key == "APPROVED"
+    }
+}
@@ -1,12 +1,14 @@
 use serde::Deserialize;
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkLog {
     #[serde(rename = "self")]
     pub self_: String,
     pub tempo_worklog_id: i32,
-    pub jira_worklog_id: i32,
+    // Not returned by the v4 API
+    pub jira_worklog_id: Option<i32>,
     pub issue: WorkLogIssue,
     pub time_spent_seconds: i32,
     pub billable_seconds: i32,
@@ -18,20 +20,24 @@ pub struct WorkLog {
     pub author: WorkLogAuthor,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkLogIssue {
     #[serde(rename = "self")]
     pub self_: String,
-    pub key: String,
+    // Not returned by the v4 API
+    pub key: Option<String>,
     pub id: i32,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkLogAuthor {
     #[serde(rename = "self")]
     pub self_: String,
     pub account_id: String,
-    pub display_name: String,
+    // Not returned by the v4 API
+    pub display_name: Option<String>,
 }
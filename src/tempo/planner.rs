@@ -0,0 +1,94 @@
+use crate::utils::date::format_duration;
+use crate::work_event::{ToWorkEvents, WorkEvent, WorkEvents};
+use chrono::NaiveDate;
+use log::info;
+use std::cmp::max;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the Tempo Planner allocations are used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannerMode {
+    // Log exactly the planned time, the unplanned time is allocated to the sprint issues
+    Fixed,
+    // Boost the time score of the planned sprint issues
+    Prior,
+}
+
+#[derive(Debug)]
+pub struct PlannedIssue {
+    pub key: String,
+    pub planned_seconds: i32,
+    pub description: String,
+}
+
+impl FromStr for PlannerMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fixed" => Ok(PlannerMode::Fixed),
+            "prior" => Ok(PlannerMode::Prior),
+            _ => Err(format!(
+                "Unknown planner mode '{}'. Expected one of: fixed, prior",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PlannerMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannerMode::Fixed => write!(f, "fixed"),
+            PlannerMode::Prior => write!(f, "prior"),
+        }
+    }
+}
+
+/// Shortens (or removes) the last planned issues, so they don't exceed the remaining time
+pub fn cap_planned_time(
+    mut planned_issues: Vec<PlannedIssue>,
+    remaining_time: i32,
+) -> Vec<PlannedIssue> {
+    let mut available_time = max(remaining_time, 0);
+    planned_issues.retain_mut(|planned_issue| {
+        if planned_issue.planned_seconds > available_time {
+            info!(
+                "Planned issue {} capped from {} to {} (day already full)",
+                planned_issue.key,
+                format_duration(&planned_issue.planned_seconds),
+                format_duration(&available_time)
+            );
+            planned_issue.planned_seconds = available_time;
+        }
+        available_time -= planned_issue.planned_seconds;
+        planned_issue.planned_seconds > 0
+    });
+
+    planned_issues
+}
+
+impl ToWorkEvents<PlannedIssue> for Vec<PlannedIssue> {
+    fn to_events(
+        self,
+        _day_duration: &i32,
+        _increment_duration: &i32,
+        _user_email: &str,
+        _date: &NaiveDate,
+        _default_issue_key: Option<&String>,
+    ) -> WorkEvents<PlannedIssue> {
+        self.into_iter()
+            .map(|planned_issue| {
+                WorkEvent::new(
+                    planned_issue.planned_seconds,
+                    1.0,
+                    planned_issue.key.to_string(),
+                    planned_issue.description.to_string(),
+                    None,
+                    planned_issue,
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TempoApiVersion {
+    // Deprecated by Tempo, identifies issues by key
+    V3,
+    // Identifies issues by id, resolved through Jira
+    V4,
+}
+
+impl TempoApiVersion {
+    pub fn base_path(&self) -> &'static str {
+        match self {
+            TempoApiVersion::V3 => "core/3",
+            TempoApiVersion::V4 => "4",
+        }
+    }
+}
+
+impl FromStr for TempoApiVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().trim_start_matches('v') {
+            "3" => Ok(TempoApiVersion::V3),
+            "4" => Ok(TempoApiVersion::V4),
+            _ => Err(format!(
+                "Unknown Tempo API version '{}'. Expected one of: 3, 4",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TempoApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TempoApiVersion::V3 => write!(f, "3"),
+            TempoApiVersion::V4 => write!(f, "4"),
+        }
+    }
+}
@@ -1,35 +1,67 @@
 use crate::http::http_client::{Credentials, HttpClient, HttpClientConfig};
+use crate::jira::jira_client::JiraClient;
+use crate::tempo::models::account::Account;
+use crate::tempo::models::account_link::ListAccountLinksResponse;
+use crate::tempo::models::list_plans_response::ListPlansResponse;
 use crate::tempo::models::list_schedules_response::ListSchedulesResponse;
 use crate::tempo::models::list_worklogs_response::ListWorkLogsResponse;
+use crate::tempo::models::plan::Plan;
 use crate::tempo::models::schedule::Schedule;
+use crate::tempo::models::timesheet_approval::TimesheetApproval;
 use crate::tempo::models::worklog::WorkLog;
+use crate::tempo::tempo_api_version::TempoApiVersion;
+use crate::tempo::work_attribute::work_attributes_for_issue;
 use crate::utils::date::{date_to_tempo_format, time_to_tempo_format};
-use crate::TempoHttpConfig;
+use crate::work_log_sink::WorkLogSink;
+use crate::{JiraHttpConfig, TempoHttpConfig};
+use async_trait::async_trait;
 use chrono::{NaiveDate, NaiveTime};
-use serde_json::{Number, Value};
+use log::warn;
+use serde_json::{json, Number, Value};
 use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Longest description accepted for a Tempo work log
+const MAX_DESCRIPTION_LENGTH: usize = 255;
 
 #[derive(Debug)]
 pub struct TempoClient {
     client: HttpClient,
     config: TempoHttpConfig,
+    // Only used with the v4 API, to resolve the issue ids from their keys
+    jira_client: Option<JiraClient>,
+    // Default Tempo account of each project (by project key)
+    account_keys: Mutex<HashMap<String, Option<String>>>,
 }
 
 impl TempoClient {
-    pub fn new(config: &TempoHttpConfig) -> Self {
+    pub fn new(config: &TempoHttpConfig, jira_config: &JiraHttpConfig) -> Self {
         Self {
             client: HttpClient::new(HttpClientConfig::new(
-                "api.tempo.io",
-                "core/3",
+                &config.url,
+                config.api_version.base_path(),
                 Credentials::Bearer(config.api_key.to_string()),
+                &config.network,
             )),
             config: config.clone(),
+            jira_client: match config.api_version {
+                TempoApiVersion::V3 => None,
+                TempoApiVersion::V4 => Some(JiraClient::new(jira_config)),
+            },
+            account_keys: Mutex::new(HashMap::new()),
         }
     }
 
     pub async fn list_schedules(&self, date: &NaiveDate) -> Vec<Schedule> {
-        let date_as_str = date_to_tempo_format(date);
-        let params = format!("from={}&to={}", &date_as_str, &date_as_str);
+        self.list_schedules_between(date, date).await
+    }
+
+    pub async fn list_schedules_between(&self, from: &NaiveDate, to: &NaiveDate) -> Vec<Schedule> {
+        let params = format!(
+            "from={}&to={}",
+            date_to_tempo_format(from),
+            date_to_tempo_format(to)
+        );
 
         self.client
             .get::<ListSchedulesResponse>(format!("user-schedule?{}", params).as_str())
@@ -37,28 +69,171 @@ impl TempoClient {
             .results
     }
 
-    pub async fn work_duration(&self, date: &NaiveDate) -> i32 {
-        return self
-            .list_schedules(date)
-            .await
-            .first()
-            .unwrap()
-            .required_seconds;
+    /// Returns the schedule of the user on the date, if Tempo has one
+    pub async fn schedule(&self, date: &NaiveDate) -> Option<Schedule> {
+        self.list_schedules(date).await.into_iter().next()
     }
 
     pub async fn list_worklogs(&self, date: &NaiveDate) -> Vec<WorkLog> {
         let date_as_str = date_to_tempo_format(date);
-        let params = format!(
-            "offset=0&limit=1000&from={}&to={}",
-            &date_as_str, &date_as_str
+
+        match self.config.api_version {
+            TempoApiVersion::V3 => {
+                let params = format!(
+                    "offset=0&limit=5000&from={}&to={}",
+                    &date_as_str, &date_as_str
+                );
+
+                self.client
+                    .get_all::<ListWorkLogsResponse>(
+                        format!("worklogs/user/{}?{}", self.config.account_id, params).as_str(),
+                    )
+                    .await
+            }
+            // The v4 API searches the work logs with the criteria in the body
+            TempoApiVersion::V4 => {
+                let mut payload = HashMap::<String, Value>::new();
+                payload.insert(
+                    "authorIds".to_string(),
+                    json!([self.config.account_id.to_string()]),
+                );
+                payload.insert("from".to_string(), Value::String(date_as_str.to_string()));
+                payload.insert("to".to_string(), Value::String(date_as_str));
+
+                self.client
+                    .post_all::<ListWorkLogsResponse>(
+                        "worklogs/search?offset=0&limit=5000",
+                        &payload,
+                    )
+                    .await
+            }
+        }
+    }
+
+    /// Lists the Tempo Planner plans of the user on the date
+    pub async fn list_plans(&self, date: &NaiveDate) -> Vec<Plan> {
+        let date_as_str = date_to_tempo_format(date);
+        let params = format!("from={}&to={}", &date_as_str, &date_as_str);
+
+        self.client
+            .get_all::<ListPlansResponse>(
+                format!("plans/user/{}?{}", self.config.account_id, params).as_str(),
+            )
+            .await
+    }
+
+    /// Returns the timesheet approval of the period containing the date
+    pub async fn get_timesheet_approval(&self, date: &NaiveDate) -> TimesheetApproval {
+        let date_as_str = date_to_tempo_format(date);
+        let params = format!("from={}&to={}", &date_as_str, &date_as_str);
+
+        self.client
+            .get::<TimesheetApproval>(
+                format!(
+                    "timesheet-approvals/user/{}?{}",
+                    self.config.account_id, params
+                )
+                .as_str(),
+            )
+            .await
+    }
+
+    /// Checks whether there is no more working day after the date, until the end of the period
+    pub async fn is_last_working_day(&self, date: &NaiveDate, period_end: &NaiveDate) -> bool {
+        if date >= period_end {
+            return true;
+        }
+
+        self.list_schedules_between(&date.succ(), period_end)
+            .await
+            .iter()
+            .all(|schedule| !schedule.is_working_day())
+    }
+
+    pub async fn submit_timesheet(
+        &self,
+        approval: &TimesheetApproval,
+        reviewer_account_id: &str,
+    ) -> TimesheetApproval {
+        let params = format!("from={}&to={}", approval.period.from, approval.period.to);
+
+        let mut payload = HashMap::<String, Value>::new();
+        payload.insert(
+            "reviewerAccountId".to_string(),
+            Value::String(reviewer_account_id.to_string()),
+        );
+        payload.insert(
+            "comment".to_string(),
+            Value::String("Submitted by tempo-bot".to_string()),
         );
 
         self.client
-            .get::<ListWorkLogsResponse>(
-                format!("worklogs/user/{}?{}", self.config.account_id, params).as_str(),
+            .post::<TimesheetApproval>(
+                format!(
+                    "timesheet-approvals/user/{}/submit?{}",
+                    self.config.account_id, params
+                )
+                .as_str(),
+                Some(&payload),
             )
             .await
+    }
+
+    /// Returns the key of the default Tempo account linked to the Jira project
+    pub async fn default_account_key(&self, project_key: &str) -> Option<String> {
+        if let Some(account_key) = self.account_keys.lock().unwrap().get(project_key) {
+            return account_key.clone();
+        }
+
+        // The v4 API identifies the projects by id
+        let project = match &self.jira_client {
+            Some(jira_client) => jira_client.get_project(project_key).await.id,
+            None => project_key.to_string(),
+        };
+
+        let default_link = self
+            .client
+            .get::<ListAccountLinksResponse>(format!("account-links/project/{}", project).as_str())
+            .await
             .results
+            .into_iter()
+            .find(|link| link.default);
+
+        let account_key = match default_link {
+            Some(link) => match link.account.key {
+                Some(key) => Some(key),
+                None => Some(
+                    self.client
+                        .get_url::<Account>(&link.account.self_)
+                        .await
+                        .key,
+                ),
+            },
+            None => None,
+        };
+
+        self.account_keys
+            .lock()
+            .unwrap()
+            .insert(project_key.to_string(), account_key.clone());
+
+        account_key
+    }
+
+    async fn work_attributes(&self, issue_key: &str) -> Vec<(String, String)> {
+        let mut attributes = work_attributes_for_issue(&self.config.work_attributes, issue_key);
+
+        // The account from the project is only a fallback, if not explicitly configured
+        if let Some(account_attribute) = &self.config.account_attribute {
+            if !attributes.iter().any(|(key, _)| key == account_attribute) {
+                let project_key = issue_key.split('-').next().unwrap_or(issue_key);
+                if let Some(account_key) = self.default_account_key(project_key).await {
+                    attributes.push((account_attribute.to_string(), account_key));
+                }
+            }
+        }
+
+        attributes
     }
 
     pub async fn post_worklog(
@@ -68,20 +243,47 @@ impl TempoClient {
         issue_key: &str,
         duration: &i32,
         description: &str,
+        billable: bool,
     ) {
         let mut payload = HashMap::<String, Value>::new();
-        payload.insert("issueKey".to_string(), Value::String(issue_key.to_string()));
+        match &self.jira_client {
+            Some(jira_client) => {
+                let issue_id = jira_client.get_issue_reference(issue_key).await.id;
+                match issue_id.parse::<i64>() {
+                    Ok(id) => {
+                        payload.insert("issueId".to_string(), Value::Number(Number::from(id)))
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Could not log the work on {}: invalid issue id '{}' ({})",
+                            issue_key, issue_id, e
+                        );
+                        return;
+                    }
+                };
+            }
+            None => {
+                payload.insert("issueKey".to_string(), Value::String(issue_key.to_string()));
+            }
+        }
         payload.insert(
             "timeSpentSeconds".to_string(),
             Value::Number(Number::from(*duration)),
         );
+        // Tempo rejects this field without the permission to edit the billable hours, so only send it when needed
+        if !billable {
+            payload.insert(
+                "billableSeconds".to_string(),
+                Value::Number(Number::from(0)),
+            );
+        }
         payload.insert(
             "startDate".to_string(),
             Value::String(date_to_tempo_format(date)),
         );
         payload.insert(
             "description".to_string(),
-            Value::String(description.to_string()),
+            Value::String(truncate(description, MAX_DESCRIPTION_LENGTH)),
         );
         payload.insert(
             "authorAccountId".to_string(),
@@ -91,7 +293,20 @@ impl TempoClient {
         if let Some(t) = time {
             payload.insert(
                 "startTime".to_string(),
-                Value::String(time_to_tempo_format(t))
+                Value::String(time_to_tempo_format(t)),
+            );
+        }
+
+        let attributes = self.work_attributes(issue_key).await;
+        if !attributes.is_empty() {
+            payload.insert(
+                "attributes".to_string(),
+                Value::Array(
+                    attributes
+                        .into_iter()
+                        .map(|(key, value)| json!({ "key": key, "value": value }))
+                        .collect(),
+                ),
             );
         }
 
@@ -100,3 +315,38 @@ impl TempoClient {
             .await;
     }
 }
+
+#[async_trait]
+impl WorkLogSink for TempoClient {
+    async fn logged_time(&self, date: &NaiveDate) -> i32 {
+        self.list_worklogs(date)
+            .await
+            .iter()
+            .map(|worklog| worklog.time_spent_seconds)
+            .sum()
+    }
+
+    async fn post_worklog(
+        &self,
+        date: &NaiveDate,
+        time: Option<&NaiveTime>,
+        issue_key: &str,
+        duration: &i32,
+        description: &str,
+        billable: bool,
+    ) {
+        TempoClient::post_worklog(self, date, time, issue_key, duration, description, billable)
+            .await
+    }
+}
+
+// Counted in characters, with an ellipsis when truncated
+fn truncate(text: &str, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_length - 1).collect();
+    truncated.push('…');
+    truncated
+}
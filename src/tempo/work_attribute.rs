@@ -0,0 +1,108 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkAttributeScope {
+    All,
+    // Project prefix of the issue keys (e.g. "ABC")
+    Project(String),
+    // A specific issue key (e.g. "ABC-123")
+    Issue(String),
+}
+
+/// A Tempo work attribute sent with the work logs. Format: '[<scope>:]<key>=<value>'
+#[derive(Debug, Clone)]
+pub struct WorkAttribute {
+    pub scope: WorkAttributeScope,
+    pub key: String,
+    pub value: String,
+}
+
+impl WorkAttributeScope {
+    fn specificity(&self) -> u8 {
+        match self {
+            WorkAttributeScope::All => 0,
+            WorkAttributeScope::Project(_) => 1,
+            WorkAttributeScope::Issue(_) => 2,
+        }
+    }
+
+    pub fn applies_to(&self, issue_key: &str) -> bool {
+        match self {
+            WorkAttributeScope::All => true,
+            WorkAttributeScope::Project(project) => issue_key
+                .split_once('-')
+                .map(|(prefix, _)| prefix.eq_ignore_ascii_case(project))
+                .unwrap_or(false),
+            WorkAttributeScope::Issue(key) => issue_key.eq_ignore_ascii_case(key),
+        }
+    }
+}
+
+impl FromStr for WorkAttribute {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid work attribute '{}'. Expected format: '[<project or issue key>:]<key>=<value>'",
+                s
+            )
+        };
+
+        let (attribute, value) = s.split_once('=').ok_or_else(invalid)?;
+        let (scope, key) = match attribute.split_once(':') {
+            Some((scope, key)) => {
+                let scope = scope.trim().to_string();
+                if scope.contains('-') {
+                    (WorkAttributeScope::Issue(scope), key)
+                } else {
+                    (WorkAttributeScope::Project(scope), key)
+                }
+            }
+            None => (WorkAttributeScope::All, attribute),
+        };
+
+        if key.trim().is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            scope,
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for WorkAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scope {
+            WorkAttributeScope::All => write!(f, "{}={}", self.key, self.value),
+            WorkAttributeScope::Project(scope) | WorkAttributeScope::Issue(scope) => {
+                write!(f, "{}:{}={}", scope, self.key, self.value)
+            }
+        }
+    }
+}
+
+/// Returns the attributes (key, value) to send for the issue. When the same key is configured
+/// several times, the most specific scope wins (issue, then project, then all issues).
+pub fn work_attributes_for_issue(
+    attributes: &[WorkAttribute],
+    issue_key: &str,
+) -> Vec<(String, String)> {
+    let mut matching: Vec<&WorkAttribute> = attributes
+        .iter()
+        .filter(|attribute| attribute.scope.applies_to(issue_key))
+        .collect();
+    matching.sort_by_key(|attribute| attribute.scope.specificity());
+
+    let mut result: Vec<(String, String)> = Vec::new();
+    for attribute in matching.into_iter() {
+        result.retain(|(key, _)| key != &attribute.key);
+        result.push((attribute.key.to_string(), attribute.value.to_string()));
+    }
+
+    result
+}
@@ -1,32 +1,49 @@
 use chrono;
-use chrono::{NaiveDate, NaiveTime, Utc};
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 
-pub fn parse_date_from_str(date_str: &str) -> NaiveDate {
-    let today = Utc::now().date().naive_utc();
+/// 'today' and 'yesterday' are relative to the time zone
+pub fn parse_date_from_str(date_str: &str, timezone: &Tz) -> NaiveDate {
+    let today = Utc::now().with_timezone(timezone).date().naive_local();
 
-    return match date_str {
+    match date_str {
         "today" => today,
         "yesterday" => today.pred(),
         _ => match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
             Ok(x) => x,
             Err(_) => panic!("Could not parse the date {}", date_str),
         },
-    };
+    }
+}
+
+/// Parses the date part of a Jira timestamp (e.g. "2021-01-17T12:34:00.000+0000")
+pub fn parse_jira_date(date_time_str: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date_time_str, "%Y-%m-%dT%H:%M:%S%.f%z").ok()
 }
 
 pub fn date_to_tempo_format(date: &NaiveDate) -> String {
-    return date.format("%Y-%m-%d").to_string();
+    date.format("%Y-%m-%d").to_string()
 }
 
 pub fn time_to_tempo_format(time: &NaiveTime) -> String {
-    return time.format("%H:%M:%S").to_string();
+    time.format("%H:%M:%S").to_string()
+}
+
+pub fn date_time_to_jira_format(date: &NaiveDate, time: &NaiveTime, timezone: &Tz) -> String {
+    let date_time = date.and_time(*time);
+    let date_time = timezone
+        .from_local_datetime(&date_time)
+        .earliest()
+        .unwrap_or_else(|| timezone.from_utc_datetime(&date_time));
+
+    date_time.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string()
 }
 
 pub fn format_duration(duration_in_seconds: &i32) -> String {
-    return format!(
+    format!(
         "{}h {}m {}s",
         duration_in_seconds / 3600,
         (duration_in_seconds / 60) % 60,
         duration_in_seconds % 60
-    );
+    )
 }
@@ -0,0 +1,10 @@
+// Parameters of the 64 bits FNV-1a hash
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Hash of the text, stable across Rust versions (unlike the `DefaultHasher`), for what's persisted on disk
+pub fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
@@ -1 +1,3 @@
 pub mod date;
+pub mod hash;
+pub mod random;
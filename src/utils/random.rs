@@ -0,0 +1,40 @@
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+use std::cell::RefCell;
+use std::future::Future;
+use std::ops::RangeInclusive;
+
+tokio::task_local! {
+    // Only seeded when recording or replaying a run, so the same time scores are computed
+    static SEEDED_RNG: RefCell<Option<StdRng>>;
+}
+
+/// Runs the future with its own randomness, so a seed never leaks to another run of the same process
+pub async fn scope<F: Future>(future: F) -> F::Output {
+    SEEDED_RNG.scope(RefCell::new(None), future).await
+}
+
+/// Only within `scope`
+pub fn set_seed(seed: u64) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+pub fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
+pub fn gen_range(range: RangeInclusive<f64>) -> f64 {
+    with_seeded_rng(|rng| rng.gen_range(range.clone()))
+        .unwrap_or_else(|| thread_rng().gen_range(range))
+}
+
+pub fn gen_u32() -> u32 {
+    with_seeded_rng(|rng| rng.gen()).unwrap_or_else(|| thread_rng().gen())
+}
+
+fn with_seeded_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> Option<T> {
+    SEEDED_RNG
+        .try_with(|rng| rng.borrow_mut().as_mut().map(f))
+        .ok()
+        .flatten()
+}
@@ -1,4 +1,4 @@
-use crate::TempoClient;
+use crate::work_log_sink::WorkLogSink;
 use chrono::{NaiveDate, NaiveTime};
 use log::info;
 
@@ -10,6 +10,7 @@ pub struct WorkEvent<T> {
     pub key: String,
     pub description: String,
     pub time: Option<NaiveTime>,
+    pub billable: bool,
     pub event: T,
 }
 
@@ -43,7 +44,7 @@ pub trait ToWorkEvents<T> {
             date,
             default_issue_key,
         );
-        events.sort_by(|a, b| b.duration.cmp(&a.duration));
+        events.sort_by_key(|event| std::cmp::Reverse(event.duration));
         events
     }
 }
@@ -63,11 +64,12 @@ impl<T> WorkEvent<T> {
             key,
             description,
             time,
+            billable: true,
             event,
         }
     }
 
-    pub async fn log_to_tempo(&self, tempo_client: &TempoClient, date: &NaiveDate) {
+    pub async fn log_to(&self, sink: &dyn WorkLogSink, date: &NaiveDate) {
         if self.duration == 0 || self.key.is_empty() {
             return;
         }
@@ -78,14 +80,14 @@ impl<T> WorkEvent<T> {
             &self.key
         );
 
-        tempo_client
-            .post_worklog(
-                date,
-                self.time.as_ref(),
-                &self.key,
-                &self.duration,
-                self.description.as_str(),
-            )
-            .await;
+        sink.post_worklog(
+            date,
+            self.time.as_ref(),
+            &self.key,
+            &self.duration,
+            self.description.as_str(),
+            self.billable,
+        )
+        .await;
     }
 }
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveTime};
+use std::fmt;
+use std::str::FromStr;
+
+/// Where the work logs are sent to (and read from, to compute the remaining time of the day)
+#[async_trait]
+pub trait WorkLogSink: Sync {
+    /// Total duration (in seconds) already logged by the user on the given date
+    async fn logged_time(&self, date: &NaiveDate) -> i32;
+
+    async fn post_worklog(
+        &self,
+        date: &NaiveDate,
+        time: Option<&NaiveTime>,
+        issue_key: &str,
+        duration: &i32,
+        description: &str,
+        // Not supported by every sink
+        billable: bool,
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkLogSinkKind {
+    Tempo,
+    Jira,
+}
+
+impl FromStr for WorkLogSinkKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "tempo" => Ok(WorkLogSinkKind::Tempo),
+            "jira" => Ok(WorkLogSinkKind::Jira),
+            _ => Err(format!(
+                "Unknown work log sink '{}'. Expected one of: tempo, jira",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WorkLogSinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkLogSinkKind::Tempo => write!(f, "tempo"),
+            WorkLogSinkKind::Jira => write!(f, "jira"),
+        }
+    }
+}
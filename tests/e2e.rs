@@ -0,0 +1,1382 @@
+mod support;
+
+use chrono::Duration;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use support::{fixture_path, logged_worklog, FakeAtlassian};
+use tempo_bot::run;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use wiremock::matchers::{body_string_contains, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const HOUR: i64 = 3600;
+
+fn time_spent(worklog: &Value) -> i64 {
+    worklog["timeSpentSeconds"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn dry_run_does_not_log_anything() {
+    let fake = FakeAtlassian::start().await;
+    let config = tempo_bot::Config {
+        dry_run: true,
+        ..fake.config()
+    };
+
+    run(config).await;
+
+    assert!(fake.posted_worklogs().await.is_empty());
+}
+
+#[tokio::test]
+async fn logs_the_remaining_time_of_the_day_to_the_user_issues() {
+    let fake = FakeAtlassian::start().await;
+
+    run(fake.config()).await;
+
+    let worklogs = fake.posted_worklogs().await;
+    let issue_keys: Vec<&str> = worklogs
+        .iter()
+        .map(|worklog| worklog["issueKey"].as_str().unwrap())
+        .collect();
+
+    // ABC-4 is neither assigned to nor reported by the user
+    assert_eq!(worklogs.len(), 2);
+    assert!(issue_keys.contains(&"ABC-2"));
+    assert!(issue_keys.contains(&"ABC-3"));
+
+    // 8h in the schedule, 1h already logged, every work log is rounded to 30 minutes
+    let total: i64 = worklogs.iter().map(time_spent).sum();
+    assert!((total - 7 * HOUR).abs() <= HOUR / 2, "total: {}", total);
+    for worklog in worklogs.iter() {
+        assert_eq!(time_spent(worklog) % (HOUR / 2), 0);
+        assert_eq!(worklog["startDate"], "2022-10-18");
+        assert_eq!(worklog["authorAccountId"], support::ACCOUNT_ID);
+    }
+}
+
+#[tokio::test]
+async fn skips_the_issues_filtered_out() {
+    let logged_issues = |issue_filters: tempo_bot::IssueFilters| async {
+        let fake = FakeAtlassian::start().await;
+        run(tempo_bot::Config {
+            issue_filters,
+            ..fake.config()
+        })
+        .await;
+
+        fake.posted_worklogs()
+            .await
+            .iter()
+            .map(|worklog| {
+                (
+                    worklog["issueKey"].as_str().unwrap().to_string(),
+                    time_spent(worklog),
+                )
+            })
+            .collect::<Vec<(String, i64)>>()
+    };
+
+    // ABC-3 is a bug, so the whole remaining time goes to ABC-2
+    let excluding_bugs = logged_issues(tempo_bot::IssueFilters {
+        include: Vec::new(),
+        exclude: vec!["type=bug".parse().unwrap()],
+    })
+    .await;
+    assert_eq!(excluding_bugs, vec![("ABC-2".to_string(), 7 * HOUR)]);
+
+    // The include filters on the same field are OR-ed
+    let to_do_or_done = logged_issues(tempo_bot::IssueFilters {
+        include: vec![
+            "status=to do".parse().unwrap(),
+            "status=done".parse().unwrap(),
+        ],
+        exclude: Vec::new(),
+    })
+    .await;
+    assert_eq!(to_do_or_done, vec![("ABC-3".to_string(), 7 * HOUR)]);
+}
+
+#[tokio::test]
+async fn follows_the_pages_of_the_tempo_worklogs_through_the_configured_url() {
+    let fake = FakeAtlassian::start().await;
+    let worklogs_path = format!("/core/3/worklogs/user/{}", support::ACCOUNT_ID);
+
+    // The next page is advertised on the public Tempo host, but must be read from the configured one
+    let mut first_page = support::fixture("worklogs.json");
+    first_page["metadata"]["next"] = Value::String(format!(
+        "https://api.tempo.io{}?offset=1&limit=1&from=2022-10-18&to=2022-10-18",
+        worklogs_path
+    ));
+    fake.mount("GET", &worklogs_path, first_page).await;
+    Mock::given(method("GET"))
+        .and(path(worklogs_path.as_str()))
+        .and(query_param("offset", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(support::fixture("worklogs.json")))
+        .with_priority(1)
+        .mount(&fake.server)
+        .await;
+
+    run(fake.config()).await;
+
+    // 8h in the schedule, 1h already logged on each page
+    let total: i64 = fake.posted_worklogs().await.iter().map(time_spent).sum();
+    assert_eq!(total, 6 * HOUR);
+}
+
+#[tokio::test]
+async fn logs_the_issues_by_id_with_the_tempo_api_v4() {
+    let fake = FakeAtlassian::start().await;
+    fake.mount(
+        "GET",
+        "/4/user-schedule",
+        support::fixture("user_schedule.json"),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/4/worklogs/search"))
+        .and(body_string_contains(support::ACCOUNT_ID))
+        .respond_with(ResponseTemplate::new(200).set_body_json(support::fixture("worklogs.json")))
+        .mount(&fake.server)
+        .await;
+    fake.mount(
+        "POST",
+        "/4/worklogs",
+        support::fixture("posted_worklog.json"),
+    )
+    .await;
+    for (key, id) in [("ABC-2", "10002"), ("ABC-3", "10003")] {
+        fake.mount(
+            "GET",
+            format!("/rest/api/3/issue/{}", key).as_str(),
+            serde_json::json!({ "id": id, "key": key }),
+        )
+        .await;
+    }
+
+    let mut config = fake.config();
+    config.tempo.as_mut().unwrap().api_version = tempo_bot::TempoApiVersion::V4;
+    run(config).await;
+
+    let worklogs: Vec<Value> = fake
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| {
+            request.method == wiremock::http::Method::Post && request.url.path() == "/4/worklogs"
+        })
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect();
+
+    let mut issue_ids: Vec<i64> = worklogs
+        .iter()
+        .map(|worklog| worklog["issueId"].as_i64().unwrap())
+        .collect();
+    issue_ids.sort();
+    assert_eq!(issue_ids, vec![10002, 10003]);
+    assert!(worklogs.iter().all(|worklog| worklog["issueKey"].is_null()));
+
+    // The work logs already found by the search are deducted from the day
+    let total: i64 = worklogs.iter().map(time_spent).sum();
+    assert_eq!(total, 7 * HOUR);
+}
+
+#[tokio::test]
+async fn logs_the_meetings_of_the_day_before_the_issues() {
+    let fake = FakeAtlassian::start().await;
+    let config = tempo_bot::Config {
+        calendars: vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar.ics",
+        ))],
+        ..fake.config()
+    };
+
+    run(config).await;
+
+    let worklogs = fake.posted_worklogs().await;
+    let meeting = worklogs.first().unwrap();
+    assert_eq!(meeting["issueKey"], "ABC-5");
+    assert_eq!(meeting["description"], "ABC-5 Sprint review");
+    assert_eq!(meeting["startTime"], "10:00:00");
+    assert_eq!(time_spent(meeting), HOUR);
+
+    // The meeting of the previous day is ignored
+    assert_eq!(
+        worklogs
+            .iter()
+            .filter(|worklog| worklog.get("startTime").is_some())
+            .count(),
+        1
+    );
+
+    // The issues fill the rest of the day
+    let total: i64 = worklogs.iter().map(time_spent).sum();
+    assert!((total - 7 * HOUR).abs() <= HOUR / 2, "total: {}", total);
+}
+
+#[tokio::test]
+async fn only_sends_the_billable_seconds_of_the_non_billable_meetings() {
+    let billable_seconds = |non_billable_meetings: bool| async move {
+        let fake = FakeAtlassian::start().await;
+        run(tempo_bot::Config {
+            calendars: vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar.ics",
+            ))],
+            non_billable_meetings,
+            ..fake.config()
+        })
+        .await;
+
+        let mut billable_seconds: Vec<(String, Value)> = fake
+            .posted_worklogs()
+            .await
+            .iter()
+            .map(|worklog| {
+                (
+                    worklog["issueKey"].as_str().unwrap().to_string(),
+                    worklog["billableSeconds"].clone(),
+                )
+            })
+            .collect();
+        billable_seconds.sort_by(|a, b| a.0.cmp(&b.0));
+        billable_seconds
+    };
+
+    // Not sent by default, as Tempo requires a permission for it
+    assert!(billable_seconds(false)
+        .await
+        .iter()
+        .all(|(_, seconds)| seconds.is_null()));
+    assert_eq!(
+        billable_seconds(true).await,
+        vec![
+            ("ABC-2".to_string(), Value::Null),
+            ("ABC-3".to_string(), Value::Null),
+            ("ABC-5".to_string(), Value::from(0)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn selects_and_weights_the_issues_by_involvement() {
+    let logged_issues = |rules: &'static [&'static str]| async move {
+        let fake = FakeAtlassian::start().await;
+        fake.mount_myself().await;
+        fake.mount(
+            "GET",
+            format!("/rest/agile/1.0/sprint/{}/issue", support::SPRINT_ID).as_str(),
+            support::fixture("sprint_issues_involvement.json"),
+        )
+        .await;
+
+        run(tempo_bot::Config {
+            involvement: tempo_bot::Involvement::default().with_rules(
+                &rules
+                    .iter()
+                    .map(|rule| rule.parse().unwrap())
+                    .collect::<Vec<tempo_bot::InvolvementRule>>(),
+            ),
+            ..fake.config()
+        })
+        .await;
+
+        fake.logged_worklogs()
+            .await
+            .into_iter()
+            .map(|(_, issue_key, minutes)| (issue_key, minutes))
+            .collect::<HashMap<String, i64>>()
+    };
+    let issue_keys = |logged: &HashMap<String, i64>| {
+        let mut issue_keys: Vec<String> = logged.keys().cloned().collect();
+        issue_keys.sort();
+        issue_keys
+    };
+
+    // Besides ABC-2 and ABC-3 (which may get no time left after the rounding of the others)
+    let other_issue_keys = |logged: &HashMap<String, i64>| {
+        let mut issue_keys = issue_keys(logged);
+        issue_keys.retain(|issue_key| issue_key != "ABC-2" && issue_key != "ABC-3");
+        issue_keys
+    };
+
+    // Assigned to ABC-2, reporter of ABC-3
+    assert_eq!(
+        issue_keys(&logged_issues(&[]).await),
+        vec!["ABC-2", "ABC-3"]
+    );
+    assert_eq!(
+        issue_keys(&logged_issues(&["reporter=off"]).await),
+        vec!["ABC-2"]
+    );
+    // Watching ABC-5
+    assert_eq!(
+        other_issue_keys(&logged_issues(&["watcher=1"]).await),
+        vec!["ABC-5"]
+    );
+    // Commented ABC-6 recently, but ABC-7 only weeks ago
+    assert_eq!(
+        other_issue_keys(&logged_issues(&["commenter=1"]).await),
+        vec!["ABC-6"]
+    );
+    // Previously assigned to ABC-8
+    assert_eq!(
+        other_issue_keys(&logged_issues(&["previous-assignee=1"]).await),
+        vec!["ABC-8"]
+    );
+
+    // The weight of the role scales the time of the issue (beyond the randomness of the scores)
+    let heavy = logged_issues(&["watcher=10"]).await;
+    assert!(heavy["ABC-5"] > heavy["ABC-2"]);
+    let light = logged_issues(&["watcher=0.1"]).await;
+    assert!(light["ABC-5"] < light["ABC-2"]);
+}
+
+#[tokio::test]
+async fn logs_the_planned_issues_within_the_remaining_time() {
+    let logged_issues = |already_logged: i64| async move {
+        let fake = FakeAtlassian::start().await;
+        fake.mount_plans().await;
+        let mut worklogs = support::fixture("worklogs.json");
+        worklogs["results"][0]["timeSpentSeconds"] = Value::from(already_logged);
+        fake.mount(
+            "GET",
+            format!("/core/3/worklogs/user/{}", support::ACCOUNT_ID).as_str(),
+            worklogs,
+        )
+        .await;
+
+        run(tempo_bot::Config {
+            planner_mode: Some(tempo_bot::PlannerMode::Fixed),
+            ..fake.config()
+        })
+        .await;
+
+        let mut logged_issues: Vec<(String, i64)> = fake
+            .posted_worklogs()
+            .await
+            .iter()
+            .map(|worklog| {
+                (
+                    worklog["issueKey"].as_str().unwrap().to_string(),
+                    time_spent(worklog),
+                )
+            })
+            .collect();
+        logged_issues.sort();
+        logged_issues
+    };
+
+    // 3h planned on ABC-9, the rest of the day goes to the sprint issues
+    let logged = logged_issues(HOUR).await;
+    assert!(logged.contains(&("ABC-9".to_string(), 3 * HOUR)));
+    assert_eq!(logged.iter().map(|(_, time)| time).sum::<i64>(), 7 * HOUR);
+
+    // The planned time is capped to the 2h left
+    assert_eq!(
+        logged_issues(6 * HOUR).await,
+        vec![("ABC-9".to_string(), 2 * HOUR)]
+    );
+}
+
+#[tokio::test]
+async fn does_not_log_anything_when_the_day_is_already_full() {
+    let fake = FakeAtlassian::start().await;
+    let mut worklogs = support::fixture("worklogs.json");
+    worklogs["results"][0]["timeSpentSeconds"] = Value::from(8 * HOUR);
+    fake.mount(
+        "GET",
+        format!("/core/3/worklogs/user/{}", support::ACCOUNT_ID).as_str(),
+        worklogs,
+    )
+    .await;
+
+    run(fake.config()).await;
+
+    assert!(fake.posted_worklogs().await.is_empty());
+}
+
+#[tokio::test]
+async fn logs_the_remaining_time_of_a_day_of_absence_to_the_absence_issue() {
+    let logged_absence = |calendar: &'static str, already_logged: i64| async move {
+        let fake = FakeAtlassian::start().await;
+        let mut worklogs = support::fixture("worklogs.json");
+        worklogs["results"][0]["timeSpentSeconds"] = Value::from(already_logged);
+        fake.mount(
+            "GET",
+            format!("/core/3/worklogs/user/{}", support::ACCOUNT_ID).as_str(),
+            worklogs,
+        )
+        .await;
+
+        run(tempo_bot::Config {
+            calendars: vec![fixture_path(calendar).parse().unwrap()],
+            absence_issue: Some("ABS-1".to_string()),
+            ..fake.config()
+        })
+        .await;
+
+        fake.posted_worklogs()
+            .await
+            .iter()
+            .map(|worklog| {
+                (
+                    worklog["issueKey"].as_str().unwrap().to_string(),
+                    time_spent(worklog),
+                )
+            })
+            .collect::<Vec<(String, i64)>>()
+    };
+
+    // The meetings of the day are not logged on a day of absence
+    assert_eq!(
+        logged_absence("calendar_vacation.ics", HOUR).await,
+        vec![("ABS-1".to_string(), 7 * HOUR)]
+    );
+    // Nor anything when more than the day is already logged
+    assert!(logged_absence("calendar_vacation.ics", 9 * HOUR)
+        .await
+        .is_empty());
+    // The absences filtered out of a calendar (e.g. the vacations of a colleague) are ignored
+    let worklogs = logged_absence("calendar_vacation.ics;exclude=title:(?i)vacation", HOUR).await;
+    assert!(worklogs.contains(&("ABC-5".to_string(), HOUR)));
+    assert!(!worklogs.iter().any(|(issue_key, _)| issue_key == "ABS-1"));
+}
+
+#[tokio::test]
+async fn submits_the_timesheet_after_the_last_working_day() {
+    let submissions = |dry_run: bool| async move {
+        let fake = FakeAtlassian::start().await;
+        let approvals_path = format!("/core/3/timesheet-approvals/user/{}", support::ACCOUNT_ID);
+        let approval = |status: &str| {
+            serde_json::json!({
+                "self": "https://api.tempo.io/core/3/timesheet-approvals/user/account-id",
+                "period": { "from": "2022-10-17", "to": support::DATE },
+                "requiredSeconds": 28800,
+                "timeSpentSeconds": 28800,
+                "status": { "key": status }
+            })
+        };
+        fake.mount("GET", &approvals_path, approval("OPEN")).await;
+        fake.mount(
+            "POST",
+            &format!("{}/submit", approvals_path),
+            approval("IN_REVIEW"),
+        )
+        .await;
+
+        run(tempo_bot::Config {
+            timesheet_reviewer: Some("reviewer-id".to_string()),
+            dry_run,
+            ..fake.config()
+        })
+        .await;
+
+        fake.server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.path().ends_with("/submit"))
+            .map(|request| serde_json::from_slice::<Value>(&request.body).unwrap())
+            .collect::<Vec<Value>>()
+    };
+
+    // The dry run only reports the submission
+    assert!(submissions(true).await.is_empty());
+
+    let submissions = submissions(false).await;
+    assert_eq!(submissions.len(), 1);
+    assert_eq!(submissions[0]["reviewerAccountId"], "reviewer-id");
+}
+
+#[tokio::test]
+async fn replays_a_recorded_run_offline() {
+    let dir = std::env::temp_dir().join(format!("tempo-bot-cassette-{}", std::process::id()));
+    let cassette = |mode| tempo_bot::NetworkConfig {
+        cassette: Some(tempo_bot::Cassette::new(mode, &dir.to_string_lossy())),
+        ..Default::default()
+    };
+    let with_network = |mut config: tempo_bot::Config, network: tempo_bot::NetworkConfig| {
+        config.jira.network = network.clone();
+        config.tempo.as_mut().unwrap().network = network;
+        config
+    };
+
+    let recorded = FakeAtlassian::start().await;
+    run(with_network(
+        recorded.config(),
+        cassette(tempo_bot::CassetteMode::Record),
+    ))
+    .await;
+    assert!(!recorded.posted_worklogs().await.is_empty());
+
+    // Credentials and emails are not stored
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert!(!content.contains(support::EMAIL));
+        assert!(!content.contains("api-key"));
+    }
+
+    // Nothing is sent when replaying, even with another email and date
+    let replayed = FakeAtlassian::start().await;
+    let replay_network = cassette(tempo_bot::CassetteMode::Replay);
+    let replay_cassette = replay_network.cassette.clone().unwrap();
+    let mut config = with_network(replayed.config(), replay_network);
+    config.jira.email = "someone.else@company.com".to_string();
+    config.date = chrono::NaiveDate::from_ymd(2022, 10, 19);
+    run(config).await;
+    assert!(replayed
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .is_empty());
+
+    // The same work logs are posted, in the same order
+    let replayed_worklogs: Vec<serde_json::Value> = replay_cassette
+        .replayed_interactions()
+        .into_iter()
+        .filter(|interaction| interaction.method == "POST" && interaction.url == "/core/3/worklogs")
+        .map(|interaction| interaction.request_body.unwrap())
+        .collect();
+    assert_eq!(replayed_worklogs, recorded.posted_worklogs().await);
+
+    // A previous recording is never mixed with a new one
+    let rerecorded = FakeAtlassian::start().await;
+    let rerecording = tokio::spawn(run(with_network(
+        rerecorded.config(),
+        cassette(tempo_bot::CassetteMode::Record),
+    )))
+    .await;
+    assert!(rerecording.unwrap_err().is_panic());
+    assert!(rerecorded.posted_worklogs().await.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn converts_the_meetings_to_the_local_time_zone() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar_timezones.ics",
+        ))];
+        config.timezone = chrono_tz::Europe::Paris;
+    })
+    .await;
+
+    let worklogs = fake.posted_worklogs().await;
+    let start_time = |issue_key: &str| {
+        worklogs
+            .iter()
+            .find(|worklog| worklog["issueKey"] == issue_key)
+            .map(|worklog| worklog["startTime"].as_str().unwrap().to_string())
+    };
+
+    // Windows zone name
+    assert_eq!(start_time("ABC-5").as_deref(), Some("09:30:00"));
+    // UTC
+    assert_eq!(start_time("ABC-6").as_deref(), Some("10:00:00"));
+    // VTIMEZONE of the calendar
+    assert_eq!(start_time("ABC-7").as_deref(), Some("16:00:00"));
+    // Recurring on Monday evenings in New York, so on Tuesday nights in Paris
+    assert_eq!(start_time("ABC-8").as_deref(), Some("02:00:00"));
+}
+
+#[tokio::test]
+async fn clips_the_multi_day_events_to_the_working_hours() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar_durations.ics",
+        ))];
+    })
+    .await;
+
+    let meetings = fake.logged_meetings().await;
+    let meeting = |issue_key: &str| {
+        meetings
+            .iter()
+            .find(|(_, key, _)| key == issue_key)
+            .map(|(start_time, _, minutes)| (start_time.as_str(), *minutes))
+    };
+
+    // DURATION instead of DTEND
+    assert_eq!(meeting("ABC-5"), Some(("14:00:00", 90)));
+    // Started the day before
+    assert_eq!(meeting("ABC-6"), Some(("09:00:00", 60)));
+    // Ends the day after
+    assert_eq!(meeting("ABC-7"), Some(("16:00:00", 120)));
+    // Crossing midnight outside of the working hours, only the part on the day is logged
+    assert_eq!(meeting("ABC-9"), Some(("00:00:00", 60)));
+    assert_eq!(meeting("ABC-10"), Some(("22:00:00", 120)));
+    // All-day events are not meetings
+    assert_eq!(meeting("ABC-8"), None);
+}
+
+#[tokio::test]
+async fn applies_the_moved_and_cancelled_occurrences_of_recurring_meetings() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar_recurrences.ics",
+        ))];
+    })
+    .await;
+
+    let meetings = fake.logged_meetings().await;
+    let meeting = |issue_key: &str| {
+        meetings
+            .iter()
+            .filter(|(_, key, _)| key == issue_key)
+            .map(|(start_time, _, minutes)| (start_time.as_str(), *minutes))
+            .collect::<Vec<(&str, i64)>>()
+    };
+
+    // Moved within the day, and logged once
+    assert_eq!(meeting("ABC-5"), vec![("11:30:00", 30)]);
+    // Cancelled
+    assert_eq!(meeting("ABC-6"), vec![]);
+    // Regular occurrence
+    assert_eq!(meeting("ABC-7"), vec![("16:00:00", 60)]);
+    // Moved from the day before
+    assert_eq!(meeting("ABC-8"), vec![("15:00:00", 45)]);
+}
+
+#[tokio::test]
+async fn clips_the_multi_day_occurrences_of_recurring_meetings() {
+    let logged_offsite = |date: &'static str| async move {
+        let fake = FakeAtlassian::run_with(|config| {
+            config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar_recurring_offsite.ics",
+            ))];
+            config.date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        })
+        .await;
+
+        let mut meetings = fake.logged_meetings().await;
+        meetings.retain(|(_, issue_key, _)| issue_key == "ABC-9");
+        meetings
+    };
+
+    // From Monday 13:00 to Tuesday 11:00, within the working hours of each day
+    assert_eq!(
+        logged_offsite("2022-10-17").await,
+        vec![logged_worklog("13:00:00", "ABC-9", 300)]
+    );
+    assert_eq!(
+        logged_offsite("2022-10-18").await,
+        vec![logged_worklog("09:00:00", "ABC-9", 120)]
+    );
+}
+
+#[tokio::test]
+async fn skips_the_declined_free_and_private_meetings() {
+    let logged_meetings = |skipped_events: &'static str| async move {
+        let fake = FakeAtlassian::run_with(|config| {
+            config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar_statuses.ics",
+            ))];
+            config.skipped_events = skipped_events.parse().unwrap();
+        })
+        .await;
+
+        let mut issue_keys: Vec<String> = fake
+            .logged_meetings()
+            .await
+            .into_iter()
+            .map(|(_, issue_key, _)| issue_key)
+            .collect();
+        issue_keys.sort();
+        issue_keys
+    };
+
+    assert_eq!(
+        logged_meetings("declined,free").await,
+        vec!["ABC-5", "ABC-7", "ABC-9"]
+    );
+    assert_eq!(
+        logged_meetings("declined,free,private").await,
+        vec!["ABC-5", "ABC-7"]
+    );
+    assert_eq!(
+        logged_meetings("declined,tentative").await,
+        vec!["ABC-5", "ABC-8", "ABC-9"]
+    );
+    assert_eq!(
+        logged_meetings("none").await,
+        vec!["ABC-5", "ABC-6", "ABC-7", "ABC-8", "ABC-9"]
+    );
+}
+
+#[tokio::test]
+async fn truncates_the_overlapping_meetings() {
+    let logged_meetings = |overlap_preferences: &'static str| async move {
+        FakeAtlassian::run_with(|config| {
+            config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar_overlaps.ics",
+            ))];
+            config.overlap_preferences = overlap_preferences.parse().unwrap();
+        })
+        .await
+        .logged_meetings()
+        .await
+    };
+
+    assert_eq!(
+        logged_meetings("shorter,earlier").await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 60),
+            logged_worklog("10:00:00", "ABC-6", 30),
+            logged_worklog("10:30:00", "ABC-5", 90),
+            logged_worklog("13:00:00", "ABC-7", 60),
+            logged_worklog("14:00:00", "ABC-8", 60),
+        ]
+    );
+    assert_eq!(
+        logged_meetings("earlier").await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 180),
+            logged_worklog("13:00:00", "ABC-7", 60),
+            logged_worklog("14:00:00", "ABC-8", 60),
+        ]
+    );
+    assert_eq!(
+        logged_meetings("later").await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 60),
+            logged_worklog("10:00:00", "ABC-6", 30),
+            logged_worklog("10:30:00", "ABC-5", 90),
+            logged_worklog("13:00:00", "ABC-7", 30),
+            logged_worklog("13:30:00", "ABC-8", 90),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn maps_the_meetings_to_issues_with_rules() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar_rules.ics",
+        ))];
+        config.meeting_rules = vec![
+            "title:(?i)sprint planning=ABC-1".parse().unwrap(),
+            r"attendee-domain:^client\.com$=CLI-42".parse().unwrap(),
+            "category:^Training$=ABC-10".parse().unwrap(),
+        ];
+        config.meeting_default_issue = Some("ABC-99".to_string());
+    })
+    .await;
+
+    let meetings: Vec<(String, String)> = fake
+        .logged_meetings()
+        .await
+        .into_iter()
+        .map(|(start_time, issue_key, _)| (start_time, issue_key))
+        .collect();
+
+    assert_eq!(
+        meetings,
+        vec![
+            ("09:00:00".to_string(), "ABC-1".to_string()),
+            // The issue key of the title comes first
+            ("10:00:00".to_string(), "ABC-5".to_string()),
+            ("11:00:00".to_string(), "CLI-42".to_string()),
+            ("12:00:00".to_string(), "ABC-99".to_string()),
+            ("14:00:00".to_string(), "ABC-10".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn merges_several_calendars_with_their_own_options() {
+    let calendar = |options: &str| -> tempo_bot::CalendarSource {
+        format!("{}{}", fixture_path(""), options).parse().unwrap()
+    };
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![
+            calendar("calendar_rules.ics;exclude=title:(?i)lunch"),
+            calendar("calendar_team.ics;default-issue=TEAM-1"),
+            calendar("calendar_on_call.ics;include=title:(?i)on-call;default-issue=OPS-3"),
+        ];
+        config.meeting_rules = vec![
+            "title:(?i)sprint planning=ABC-1".parse().unwrap(),
+            r"attendee-domain:^client\.com$=CLI-42".parse().unwrap(),
+            "category:^Training$=ABC-10".parse().unwrap(),
+        ];
+    })
+    .await;
+
+    assert_eq!(
+        fake.logged_meetings().await,
+        vec![
+            logged_worklog("09:00:00", "ABC-1", 60),
+            // The team retrospective covers the shorter meeting of the work calendar
+            logged_worklog("10:00:00", "TEAM-1", 60),
+            logged_worklog("11:00:00", "CLI-42", 30),
+            logged_worklog("12:00:00", "TEAM-1", 60),
+            logged_worklog("14:00:00", "ABC-10", 60),
+            logged_worklog("16:00:00", "OPS-3", 30),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn queries_the_events_of_the_day_from_a_caldav_server() {
+    let fake = FakeAtlassian::start().await;
+    Mock::given(method("REPORT"))
+        .and(path("/dav/calendars/john/work/"))
+        .and(header("Depth", "1"))
+        // john:secret
+        .and(header("Authorization", "Basic am9objpzZWNyZXQ="))
+        // The whole day in Paris
+        .and(body_string_contains(
+            r#"<C:time-range start="20221017T220000Z" end="20221018T220000Z"/>"#,
+        ))
+        .respond_with(
+            ResponseTemplate::new(207).set_body_string(
+                std::fs::read_to_string(fixture_path("caldav_report.xml")).unwrap(),
+            ),
+        )
+        .mount(&fake.server)
+        .await;
+    std::env::set_var("TEMPO_BOT_TEST_CALDAV_PASSWORD", "secret");
+    let config = tempo_bot::Config {
+        calendars: vec![format!(
+            "caldav+{}/dav/calendars/john/work/;user=john;password-env=TEMPO_BOT_TEST_CALDAV_PASSWORD",
+            fake.server.uri()
+        )
+        .parse()
+        .unwrap()],
+        timezone: chrono_tz::Europe::Paris,
+        ..fake.config()
+    };
+
+    run(config).await;
+
+    assert_eq!(
+        fake.logged_meetings().await,
+        vec![
+            logged_worklog("10:00:00", "ABC-5", 60),
+            logged_worklog("14:00:00", "ABC-6", 30),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn skips_the_malformed_events_of_every_calendar() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar_malformed.ics",
+        ))];
+    })
+    .await;
+
+    let meetings: Vec<(String, String)> = fake
+        .logged_meetings()
+        .await
+        .into_iter()
+        .map(|(start_time, issue_key, _)| (start_time, issue_key))
+        .collect();
+
+    assert_eq!(
+        meetings,
+        vec![
+            ("09:30:00".to_string(), "ABC-5".to_string()),
+            // In the second calendar, without any title
+            ("14:00:00".to_string(), "ABC-9".to_string()),
+            ("16:00:00".to_string(), "ABC-10".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn downloads_the_calendars_through_the_proxy() {
+    let fake = FakeAtlassian::start().await;
+    Mock::given(method("GET"))
+        .and(path("/calendar.ics"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(std::fs::read_to_string(fixture_path("calendar.ics")).unwrap()),
+        )
+        .expect(1)
+        .mount(&fake.server)
+        .await;
+
+    // The calendar host only exists behind the proxy
+    let mut config = tempo_bot::Config {
+        calendars: vec!["http://calendar.company.invalid/calendar.ics"
+            .parse()
+            .unwrap()],
+        ..fake.config()
+    };
+    config.jira.network.proxy = Some(fake.server.uri());
+    run(config).await;
+
+    let meetings: Vec<String> = fake
+        .logged_meetings()
+        .await
+        .into_iter()
+        .map(|(_, issue_key, _)| issue_key)
+        .collect();
+    assert_eq!(meetings, vec!["ABC-5".to_string()]);
+}
+
+#[tokio::test]
+async fn caches_the_downloaded_calendars() {
+    let dir = std::env::temp_dir().join(format!("tempo-bot-calendars-{}", std::process::id()));
+    let calendar_server = MockServer::start().await;
+    let calendar_url = format!("{}/calendar.ics", calendar_server.uri());
+    let ics = std::fs::read_to_string(fixture_path("calendar.ics")).unwrap();
+
+    // Issue key of the meeting logged by a run
+    let logged_meeting = |options: &'static str, max_staleness: Duration| {
+        let calendar_url = calendar_url.clone();
+        let dir = dir.clone();
+        async move {
+            let fake = FakeAtlassian::start().await;
+            let config = tempo_bot::Config {
+                calendars: vec![format!("{}{}", calendar_url, options).parse().unwrap()],
+                calendar_cache: Some(tempo_bot::CalendarCache {
+                    dir: dir.to_string_lossy().to_string(),
+                    max_staleness,
+                }),
+                ..fake.config()
+            };
+
+            tokio::spawn(run(config)).await.ok()?;
+
+            fake.posted_worklogs()
+                .await
+                .iter()
+                .find(|worklog| worklog.get("startTime").is_some())
+                .map(|worklog| worklog["issueKey"].as_str().unwrap().to_string())
+        }
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/calendar.ics"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_string(ics),
+        )
+        .expect(1)
+        .mount(&calendar_server)
+        .await;
+    assert_eq!(
+        logged_meeting("", Duration::hours(24)).await.as_deref(),
+        Some("ABC-5")
+    );
+    calendar_server.verify().await;
+
+    // Only readable by the user, and without any temporary file left
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        assert!(!path.to_string_lossy().ends_with(".tmp"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    // Not downloaded again while it's unchanged
+    calendar_server.reset().await;
+    Mock::given(method("GET"))
+        .and(path("/calendar.ics"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&calendar_server)
+        .await;
+    assert_eq!(
+        logged_meeting("", Duration::hours(24)).await.as_deref(),
+        Some("ABC-5")
+    );
+    calendar_server.verify().await;
+
+    // The cached copy is used when the download fails, unless it's too old
+    calendar_server.reset().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&calendar_server)
+        .await;
+    assert_eq!(
+        logged_meeting("", Duration::hours(24)).await.as_deref(),
+        Some("ABC-5")
+    );
+    assert_eq!(logged_meeting("", Duration::zero()).await, None);
+    // Nor by another user
+    assert_eq!(
+        logged_meeting(";user=jane;password=secret", Duration::hours(24)).await,
+        None
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn uses_the_cached_calendar_when_the_download_is_interrupted() {
+    let dir = std::env::temp_dir().join(format!(
+        "tempo-bot-interrupted-calendars-{}",
+        std::process::id()
+    ));
+    let ics = std::fs::read_to_string(fixture_path("calendar.ics")).unwrap();
+    let complete = Arc::new(AtomicBool::new(true));
+
+    // The connection is closed in the middle of the body when the download is not complete
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let calendar_url = format!("http://{}/calendar.ics", listener.local_addr().unwrap());
+    let server_complete = complete.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let _ = socket.read(&mut request).await;
+            let body = match server_complete.load(Ordering::SeqCst) {
+                true => &ics[..],
+                false => &ics[..ics.len() / 2],
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                ics.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+
+    let logged_meeting = || {
+        let calendar_url = calendar_url.clone();
+        let dir = dir.clone();
+        async move {
+            let fake = FakeAtlassian::start().await;
+            run(tempo_bot::Config {
+                calendars: vec![calendar_url.parse().unwrap()],
+                calendar_cache: Some(tempo_bot::CalendarCache {
+                    dir: dir.to_string_lossy().to_string(),
+                    max_staleness: Duration::hours(24),
+                }),
+                ..fake.config()
+            })
+            .await;
+
+            fake.logged_meetings()
+                .await
+                .into_iter()
+                .map(|(_, issue_key, _)| issue_key)
+                .collect::<Vec<String>>()
+        }
+    };
+
+    assert_eq!(logged_meeting().await, vec!["ABC-5".to_string()]);
+    complete.store(false, Ordering::SeqCst);
+    assert_eq!(logged_meeting().await, vec!["ABC-5".to_string()]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn rounds_the_meetings_and_merges_the_short_ones() {
+    let run_with_durations = |meeting_durations: tempo_bot::MeetingDurations| {
+        FakeAtlassian::run_with(|config| {
+            config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar_short_meetings.ics",
+            ))];
+            config.meeting_durations = meeting_durations;
+            config.meeting_description = tempo_bot::DescriptionTemplate::new("{start}-{end}");
+        })
+    };
+
+    assert_eq!(
+        run_with_durations(tempo_bot::MeetingDurations::default())
+            .await
+            .logged_meetings()
+            .await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 25),
+            logged_worklog("10:00:00", "ABC-6", 10),
+            logged_worklog("14:00:00", "ABC-7", 40),
+        ]
+    );
+    assert_eq!(
+        run_with_durations(tempo_bot::MeetingDurations {
+            rounding: "nearest".parse().unwrap(),
+            increment_seconds: 15 * 60,
+            min_duration_seconds: 15 * 60,
+            misc_issue: None,
+        })
+        .await
+        .logged_meetings()
+        .await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 30),
+            logged_worklog("14:00:00", "ABC-7", 45),
+        ]
+    );
+
+    // The standup and the coffee chat are logged together
+    let fake = run_with_durations(tempo_bot::MeetingDurations {
+        rounding: "up".parse().unwrap(),
+        increment_seconds: 30 * 60,
+        min_duration_seconds: 15 * 60,
+        misc_issue: Some("ABC-99".to_string()),
+    })
+    .await;
+    assert_eq!(
+        fake.logged_meetings().await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 30),
+            logged_worklog("10:00:00", "ABC-99", 30),
+            logged_worklog("14:00:00", "ABC-7", 60),
+        ]
+    );
+
+    // The end times follow the merged and rounded durations
+    let mut descriptions = fake
+        .posted_worklogs()
+        .await
+        .iter()
+        .filter(|worklog| worklog.get("startTime").is_some())
+        .map(|worklog| worklog["description"].as_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    descriptions.sort();
+    assert_eq!(
+        descriptions,
+        vec!["09:00-09:30", "10:00-10:30", "14:00-15:00"]
+    );
+}
+
+#[tokio::test]
+async fn does_not_overlap_the_merged_short_meetings_with_the_other_ones() {
+    let logged_meetings = |overlap_preferences: &'static str| async move {
+        FakeAtlassian::run_with(|config| {
+            config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar_short_meetings_around.ics",
+            ))];
+            config.overlap_preferences = overlap_preferences.parse().unwrap();
+            config.meeting_durations = tempo_bot::MeetingDurations {
+                min_duration_seconds: 15 * 60,
+                misc_issue: Some("ABC-99".to_string()),
+                ..tempo_bot::MeetingDurations::default()
+            };
+        })
+        .await
+        .logged_meetings()
+        .await
+    };
+
+    // The merged meeting starts with the coffee chat, and runs into the standup
+    assert_eq!(
+        logged_meetings("longer,earlier").await,
+        vec![
+            logged_worklog("10:00:00", "ABC-99", 10),
+            logged_worklog("10:10:00", "ABC-6", 30),
+        ]
+    );
+    assert_eq!(
+        logged_meetings("shorter,earlier").await,
+        vec![
+            logged_worklog("10:00:00", "ABC-99", 15),
+            logged_worklog("10:15:00", "ABC-6", 25),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn applies_the_overtime_policy_to_the_meetings() {
+    let logged_worklogs = |meeting_overtime: &'static str,
+                           planner_mode: Option<tempo_bot::PlannerMode>| async move {
+        let fake = FakeAtlassian::start().await;
+        fake.mount_plans().await;
+        let config = tempo_bot::Config {
+            calendars: vec![tempo_bot::CalendarSource::new(&fixture_path(
+                "calendar_long_day.ics",
+            ))],
+            meeting_overtime: meeting_overtime.parse().unwrap(),
+            planner_mode,
+            ..fake.config()
+        };
+
+        run(config).await;
+
+        fake.logged_worklogs().await
+    };
+
+    // 10 hours of meetings for the 7 remaining hours, and no time left for the issues
+    assert_eq!(
+        logged_worklogs("log", None).await,
+        vec![
+            logged_worklog("08:00:00", "ABC-8", 60),
+            logged_worklog("09:00:00", "ABC-5", 180),
+            logged_worklog("13:00:00", "ABC-6", 240),
+            logged_worklog("17:00:00", "ABC-7", 120),
+        ]
+    );
+    assert_eq!(
+        logged_worklogs("cap", None).await,
+        vec![
+            logged_worklog("08:00:00", "ABC-8", 60),
+            logged_worklog("09:00:00", "ABC-5", 180),
+            logged_worklog("13:00:00", "ABC-6", 180),
+        ]
+    );
+    assert_eq!(
+        logged_worklogs("working-hours", None).await,
+        vec![
+            logged_worklog("09:00:00", "ABC-5", 180),
+            logged_worklog("13:00:00", "ABC-6", 240),
+            logged_worklog("17:00:00", "ABC-7", 60),
+        ]
+    );
+    // Nor for the 3 planned hours, the planned issues never get a negative time
+    assert_eq!(
+        logged_worklogs("log", Some(tempo_bot::PlannerMode::Fixed)).await,
+        logged_worklogs("log", None).await
+    );
+}
+
+#[tokio::test]
+async fn describes_the_worklogs_with_templates() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.calendars = vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar.ics",
+        ))];
+        config.issue_description = "{summary} [{status}] ({sprint}) {run}".parse().unwrap();
+        config.meeting_description = "Meeting: {title} ({attendees} people) {run}"
+            .parse()
+            .unwrap();
+    })
+    .await;
+
+    let worklogs = fake.posted_worklogs().await;
+    let description = |issue_key: &str| {
+        worklogs
+            .iter()
+            .find(|worklog| worklog["issueKey"] == issue_key)
+            .map(|worklog| worklog["description"].as_str().unwrap().to_string())
+            .unwrap()
+    };
+    let run_marker = description("ABC-5").rsplit(' ').next().unwrap().to_string();
+
+    assert!(run_marker.starts_with("tempo-bot#"), "{}", run_marker);
+    assert_eq!(
+        description("ABC-5"),
+        format!("Meeting: ABC-5 Sprint review (0 people) {}", run_marker)
+    );
+    assert_eq!(
+        description("ABC-2"),
+        format!(
+            "Implement the login page [In Progress] (ABC Sprint 12) {}",
+            run_marker
+        )
+    );
+    assert_eq!(
+        description("ABC-3"),
+        format!(
+            "Fix the logout button [To Do] (ABC Sprint 12) {}",
+            run_marker
+        )
+    );
+}
+
+#[tokio::test]
+async fn logs_the_remaining_time_to_the_jira_worklogs() {
+    let fake = FakeAtlassian::start().await;
+    fake.mount_jira_worklogs().await;
+    // Only the user's work logs of the date are searched and counted (2h, while Tempo has 1h)
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/search"))
+        .and(query_param(
+            "jql",
+            r#"worklogAuthor = currentUser() AND worklogDate = "2022-10-18""#,
+        ))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(support::fixture("jira_search.json")),
+        )
+        .expect(1)
+        .mount(&fake.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/api/3/issue/ABC-1/worklog"))
+        .and(query_param("startedAfter", "1665964800000"))
+        .and(query_param("startedBefore", "1666224000000"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(support::fixture("jira_worklogs.json")),
+        )
+        .expect(1)
+        .mount(&fake.server)
+        .await;
+
+    run(tempo_bot::Config {
+        calendars: vec![tempo_bot::CalendarSource::new(&fixture_path(
+            "calendar.ics",
+        ))],
+        worklog_sink: tempo_bot::WorkLogSinkKind::Jira,
+        timezone: chrono_tz::Europe::Paris,
+        ..fake.config()
+    })
+    .await;
+
+    let worklogs = fake.posted_jira_worklogs().await;
+    assert!(fake.posted_worklogs().await.is_empty());
+    assert_eq!(
+        worklogs
+            .iter()
+            .map(|(_, worklog)| time_spent(worklog))
+            .sum::<i64>(),
+        6 * HOUR
+    );
+    // The meetings at their time, the issues at noon, in the time zone of the user
+    let (_, meeting) = worklogs
+        .iter()
+        .find(|(issue_key, _)| issue_key == "ABC-5")
+        .unwrap();
+    assert_eq!(meeting["started"], "2022-10-18T10:00:00.000+0200");
+    assert_eq!(time_spent(meeting), HOUR);
+    assert_eq!(
+        meeting["comment"]["content"][0]["content"][0]["text"],
+        "ABC-5 Sprint review"
+    );
+    assert!(worklogs
+        .iter()
+        .filter(|(issue_key, _)| issue_key != "ABC-5")
+        .all(|(_, worklog)| worklog["started"] == "2022-10-18T12:00:00.000+0200"));
+}
+
+#[tokio::test]
+async fn truncates_the_long_descriptions() {
+    let fake = FakeAtlassian::run_with(|config| {
+        config.issue_description = "{summary} ".repeat(30).parse().unwrap();
+    })
+    .await;
+
+    for worklog in fake.posted_worklogs().await {
+        let description = worklog["description"].as_str().unwrap();
+        assert_eq!(description.chars().count(), 255);
+        assert!(description.ends_with('…'));
+    }
+
+    // Only Tempo limits the descriptions
+    let fake = FakeAtlassian::start().await;
+    fake.mount_jira_worklogs().await;
+    run(tempo_bot::Config {
+        issue_description: "{summary} ".repeat(30).parse().unwrap(),
+        worklog_sink: tempo_bot::WorkLogSinkKind::Jira,
+        ..fake.config()
+    })
+    .await;
+
+    let worklogs = fake.posted_jira_worklogs().await;
+    assert!(!worklogs.is_empty());
+    for (_, worklog) in worklogs {
+        let description = worklog["comment"]["content"][0]["content"][0]["text"]
+            .as_str()
+            .unwrap();
+        assert!(description.chars().count() > 255);
+    }
+}
@@ -0,0 +1,257 @@
+use chrono::NaiveDate;
+use serde_json::Value;
+use std::fs;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tempo_bot::run;
+use tempo_bot::{
+    Config, DescriptionTemplate, Involvement, IssueFilters, JiraHttpConfig, MeetingDurations,
+    MeetingOvertime, NetworkConfig, OverlapPreferences, SkippedEvents, TempoApiVersion,
+    TempoHttpConfig, WorkLogSinkKind, WorkingHours,
+};
+use wiremock::matchers::{method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+pub const DATE: &str = "2022-10-18";
+pub const EMAIL: &str = "john.doe@company.com";
+pub const ACCOUNT_ID: &str = "account-id";
+pub const BOARD_ID: i32 = 1;
+pub const SPRINT_ID: i32 = 42;
+
+pub fn fixture_path(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+pub fn fixture(name: &str) -> Value {
+    serde_json::from_str(&fs::read_to_string(fixture_path(name)).unwrap()).unwrap()
+}
+
+/// Start time (empty for the issues), issue key and duration in minutes of a posted work log
+pub type LoggedWorklog = (String, String, i64);
+
+pub fn logged_worklog(start_time: &str, issue_key: &str, minutes: i64) -> LoggedWorklog {
+    (start_time.to_string(), issue_key.to_string(), minutes)
+}
+
+/// In-process fake of the Jira and Tempo APIs, serving the fixtures and recording the requests
+pub struct FakeAtlassian {
+    pub server: MockServer,
+    // wiremock uses the matching mock with the lowest priority value first
+    next_priority: AtomicU8,
+}
+
+impl FakeAtlassian {
+    pub async fn start() -> Self {
+        let fake = Self {
+            server: MockServer::start().await,
+            next_priority: AtomicU8::new(u8::MAX),
+        };
+
+        fake.mount(
+            "GET",
+            "/core/3/user-schedule",
+            fixture("user_schedule.json"),
+        )
+        .await;
+        fake.mount(
+            "GET",
+            format!("/core/3/worklogs/user/{}", ACCOUNT_ID).as_str(),
+            fixture("worklogs.json"),
+        )
+        .await;
+        fake.mount("POST", "/core/3/worklogs", fixture("posted_worklog.json"))
+            .await;
+        fake.mount(
+            "GET",
+            format!("/rest/agile/1.0/board/{}/configuration", BOARD_ID).as_str(),
+            fixture("board_configuration.json"),
+        )
+        .await;
+        fake.mount(
+            "GET",
+            format!("/rest/agile/1.0/board/{}/sprint", BOARD_ID).as_str(),
+            fixture("sprints.json"),
+        )
+        .await;
+        fake.mount(
+            "GET",
+            format!("/rest/agile/1.0/sprint/{}/issue", SPRINT_ID).as_str(),
+            fixture("sprint_issues.json"),
+        )
+        .await;
+
+        fake
+    }
+
+    /// Serves the JSON body for the method and path. Mocks mounted last take precedence.
+    pub async fn mount(&self, http_method: &str, url_path: &str, body: Value) {
+        Mock::given(method(http_method))
+            .and(path(url_path))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .with_priority(self.next_priority.fetch_sub(1, Ordering::SeqCst))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serves the Jira user of the configuration
+    pub async fn mount_myself(&self) {
+        self.mount("GET", "/rest/api/3/myself", fixture("myself.json"))
+            .await;
+    }
+
+    /// Serves the Tempo Planner plans of the user (3h on ABC-9), and the id of the planned issue
+    pub async fn mount_plans(&self) {
+        self.mount(
+            "GET",
+            format!("/core/3/plans/user/{}", ACCOUNT_ID).as_str(),
+            fixture("plans.json"),
+        )
+        .await;
+        self.mount(
+            "GET",
+            "/rest/api/3/issue/10009",
+            serde_json::json!({ "id": "10009", "key": "ABC-9" }),
+        )
+        .await;
+    }
+
+    /// Serves the Jira work logs of the user (2h on ABC-1 on the date), and accepts the new ones
+    pub async fn mount_jira_worklogs(&self) {
+        self.mount_myself().await;
+        self.mount("GET", "/rest/api/3/search", fixture("jira_search.json"))
+            .await;
+        self.mount(
+            "GET",
+            "/rest/api/3/issue/ABC-1/worklog",
+            fixture("jira_worklogs.json"),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path_regex(r"^/rest/api/3/issue/[A-Z]+-\d+/worklog$"))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(fixture("jira_posted_worklog.json")),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Issue keys and bodies of the work logs posted to Jira, in order
+    pub async fn posted_jira_worklogs(&self) -> Vec<(String, Value)> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.method == wiremock::http::Method::Post)
+            .filter_map(|request| {
+                let issue_key = request
+                    .url
+                    .path()
+                    .strip_prefix("/rest/api/3/issue/")?
+                    .strip_suffix("/worklog")?;
+                Some((
+                    issue_key.to_string(),
+                    serde_json::from_slice(&request.body).unwrap(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Bodies of the work logs posted to Tempo, in order
+    pub async fn posted_worklogs(&self) -> Vec<Value> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| {
+                request.method == wiremock::http::Method::Post
+                    && request.url.path() == "/core/3/worklogs"
+            })
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .collect()
+    }
+
+    /// The posted work logs, sorted by start time (the issues first)
+    pub async fn logged_worklogs(&self) -> Vec<LoggedWorklog> {
+        let mut worklogs: Vec<LoggedWorklog> = self
+            .posted_worklogs()
+            .await
+            .iter()
+            .map(|worklog| {
+                logged_worklog(
+                    worklog["startTime"].as_str().unwrap_or(""),
+                    worklog["issueKey"].as_str().unwrap(),
+                    worklog["timeSpentSeconds"].as_i64().unwrap() / 60,
+                )
+            })
+            .collect();
+        worklogs.sort();
+        worklogs
+    }
+
+    /// The posted work logs of the meetings (with a start time), sorted by start time
+    pub async fn logged_meetings(&self) -> Vec<LoggedWorklog> {
+        let mut meetings = self.logged_worklogs().await;
+        meetings.retain(|(start_time, _, _)| !start_time.is_empty());
+        meetings
+    }
+
+    /// Runs the bot against a new fake server, with the default configuration edited by the test
+    pub async fn run_with(config_edit: impl FnOnce(&mut Config)) -> Self {
+        let fake = Self::start().await;
+        let mut config = fake.config();
+        config_edit(&mut config);
+        run(config).await;
+        fake
+    }
+
+    /// A configuration targeting the fake server, logging to Tempo without any confirmation
+    pub fn config(&self) -> Config {
+        Config {
+            jira: JiraHttpConfig {
+                host: self.server.uri(),
+                email: EMAIL.to_string(),
+                api_key: "jira-api-key".to_string(),
+                network: NetworkConfig::default(),
+            },
+            tempo: Some(TempoHttpConfig {
+                url: self.server.uri(),
+                network: NetworkConfig::default(),
+                api_key: "tempo-api-key".to_string(),
+                account_id: ACCOUNT_ID.to_string(),
+                api_version: TempoApiVersion::V3,
+                work_attributes: Vec::new(),
+                account_attribute: None,
+            }),
+            worklog_sink: WorkLogSinkKind::Tempo,
+            board_id: BOARD_ID,
+            sprint_prefix: "ABC Sprint".to_string(),
+            project_prefixes: vec!["ABC".to_string()],
+            issue_filters: IssueFilters::default(),
+            involvement: Involvement::default(),
+            planner_mode: None,
+            date: NaiveDate::parse_from_str(DATE, "%Y-%m-%d").unwrap(),
+            timezone: chrono_tz::UTC,
+            working_hours: WorkingHours::default(),
+            skipped_events: SkippedEvents::default(),
+            overlap_preferences: OverlapPreferences::default(),
+            target_workday_duration_seconds: None,
+            work_increment_seconds: 30 * 60,
+            calendars: Vec::new(),
+            calendar_cache: None,
+            meeting_rules: Vec::new(),
+            meeting_default_issue: None,
+            meeting_durations: MeetingDurations::default(),
+            meeting_overtime: MeetingOvertime::default(),
+            issue_description: DescriptionTemplate::new(""),
+            meeting_description: DescriptionTemplate::new("{title}"),
+            planned_issue_description: DescriptionTemplate::new("{description}"),
+            non_billable_meetings: false,
+            absence_pattern: "(?i)vacation".parse().unwrap(),
+            absence_issue: None,
+            timesheet_reviewer: None,
+            dry_run: false,
+            skip_confirmation: true,
+        }
+    }
+}